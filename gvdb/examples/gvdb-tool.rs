@@ -0,0 +1,46 @@
+//! Command line tool for working with GVDB files
+//!
+//! ```text
+//! cargo run --example gvdb-tool -- convert-endianness <little|big> <input> <output>
+//! ```
+
+use gvdb::read::GvdbFile;
+use gvdb::write::GvdbFileWriter;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: gvdb-tool convert-endianness <little|big> <input> <output>";
+
+fn convert_endianness(args: &[String]) -> Result<(), String> {
+    let [endianness, input, output] = args else {
+        return Err(USAGE.to_string());
+    };
+
+    let writer = match endianness.as_str() {
+        "little" => GvdbFileWriter::new(),
+        "big" => GvdbFileWriter::for_big_endian(),
+        _ => return Err(format!("Unknown endianness '{}'\n{}", endianness, USAGE)),
+    };
+
+    let file = GvdbFile::from_file(&PathBuf::from(input)).map_err(|err| err.to_string())?;
+    let data = writer
+        .write_to_vec_with_file(&file)
+        .map_err(|err| err.to_string())?;
+    std::fs::write(output, data).map_err(|err| format!("Error writing '{}': {}", output, err))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("convert-endianness") => convert_endianness(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,9 +1,15 @@
 mod builder;
 mod error;
+#[cfg(feature = "gio")]
+mod gio;
+mod reader;
 mod xml;
 
+#[cfg(feature = "gio")]
+pub use self::gio::register_static;
 pub use self::xml::{GResourceXMLDocument, PreprocessOptions};
 pub use builder::{GResourceBuilder, GResourceFileData};
 pub use error::{
     GResourceBuilderError, GResourceBuilderResult, GResourceXMLError, GResourceXMLResult,
 };
+pub use reader::GResourceReader;
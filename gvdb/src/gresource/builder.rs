@@ -1,14 +1,15 @@
 use crate::gresource::error::{GResourceBuilderError, GResourceBuilderResult};
 use crate::gresource::xml::PreprocessOptions;
-use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+use crate::write::{GvdbFileWriter, GvdbHashTableBuilder, GvdbValueStream};
 use flate2::write::ZlibEncoder;
 use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use walkdir::WalkDir;
 
-const FLAG_COMPRESSED: u32 = 1 << 0;
+pub(crate) const FLAG_COMPRESSED: u32 = 1 << 0;
 
 static SKIPPED_FILE_NAMES_DEFAULT: &[&str] = &["meson.build", "gresource.xml", ".gitignore"];
 static COMPRESS_EXTENSIONS_DEFAULT: &[&str] = &[".ui", ".css"];
@@ -31,7 +32,7 @@ static COMPRESS_EXTENSIONS_DEFAULT: &[&str] = &[".ui", ".css"];
 #[derive(Debug)]
 pub struct GResourceFileData<'a> {
     key: String,
-    data: Cow<'a, [u8]>,
+    data: GResourceFileContent<'a>,
     flags: u32,
 
     /// uncompressed data is zero-terminated
@@ -81,12 +82,41 @@ impl<'a> GResourceFileData<'a> {
 
         Ok(Self {
             key,
-            data,
+            data: GResourceFileContent::Data(data),
             flags,
             size,
         })
     }
 
+    /// Create an uncompressed `GResourceFileData` whose `size` bytes are read from `reader` only
+    /// when the GResource file is written
+    ///
+    /// The data is copied directly into the output of
+    /// [`GResourceBuilder::write`](crate::gresource::GResourceBuilder::write), so it is never
+    /// held in memory as a whole. No preprocessing or compression is applied. Writing fails if
+    /// the reader ends before `size` bytes were read, additional data of the reader is ignored.
+    ///
+    /// ```
+    /// use gvdb::gresource::{GResourceBuilder, GResourceFileData};
+    ///
+    /// let file = std::fs::File::open("test-data/gresource/json/test.json").unwrap();
+    /// let size = file.metadata().unwrap().len() as u32;
+    /// let file_data = GResourceFileData::from_reader("/my/app/id/test.json".to_string(), file, size);
+    ///
+    /// let mut output = Vec::new();
+    /// GResourceBuilder::from_file_data(vec![file_data])
+    ///     .write(&mut output)
+    ///     .unwrap();
+    /// ```
+    pub fn from_reader(key: String, reader: impl Read + Send + 'static, size: u32) -> Self {
+        Self {
+            key,
+            data: GResourceFileContent::Reader(Box::new(reader)),
+            flags: 0,
+            size,
+        }
+    }
+
     /// Read the data from a file
     ///
     /// Preprocessing will be applied based on the `preprocess` parameter.
@@ -212,6 +242,37 @@ impl<'a> GResourceFileData<'a> {
     pub fn key(&self) -> &str {
         &self.key
     }
+
+    /// The GVDB value this file is stored as, which is only read when it is written
+    fn into_stream(self) -> GvdbValueStream<'a> {
+        let header = vec![self.size, self.flags];
+        match self.data {
+            GResourceFileContent::Data(data) => GvdbValueStream::from_data(header, data, "(uuay)"),
+            GResourceFileContent::Reader(reader) => {
+                // Uncompressed data is zero-terminated, right after the announced size
+                let reader = reader.take(self.size as u64).chain(&[0u8] as &[u8]);
+                GvdbValueStream::new(header, reader, self.size as u64 + 1, "(uuay)")
+            }
+        }
+    }
+}
+
+/// The stored data of a GResource file
+enum GResourceFileContent<'a> {
+    /// Preprocessed and possibly compressed data
+    Data(Cow<'a, [u8]>),
+
+    /// Uncompressed data that is read when the GResource file is written
+    Reader(Box<dyn Read + Send>),
+}
+
+impl<'a> Debug for GResourceFileContent<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Data(data) => f.debug_tuple("Data").field(data).finish(),
+            Self::Reader(_) => f.debug_tuple("Reader").finish(),
+        }
+    }
 }
 
 /// GResource data value
@@ -222,9 +283,9 @@ impl<'a> GResourceFileData<'a> {
 /// The flags only indicate whether a file is compressed or not. (Compressed = 1)
 #[derive(zvariant::Type, zvariant::Value, zvariant::OwnedValue)]
 pub struct GResourceData {
-    size: u32,
-    flags: u32,
-    data: Vec<u8>,
+    pub(crate) size: u32,
+    pub(crate) flags: u32,
+    pub(crate) data: Vec<u8>,
 }
 
 /// Create a GResource binary file
@@ -288,6 +349,29 @@ impl<'a> GResourceBuilder<'a> {
         Ok(Self { files })
     }
 
+    /// Like [`from_xml()`](Self::from_xml), but all files are read, preprocessed and compressed on
+    /// a separate thread, so async executors are not blocked
+    ///
+    /// All files are processed on one new OS thread that is spawned for this call.
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{GResourceBuilder, GResourceXMLDocument};
+    ///
+    /// # futures_executor::block_on(async {
+    /// let path = PathBuf::from("test-data/gresource/test3.gresource.xml");
+    /// let doc = GResourceXMLDocument::from_file_async(&path).await.unwrap();
+    /// let builder = GResourceBuilder::from_xml_async(doc).await.unwrap();
+    /// let data = builder.build().unwrap();
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn from_xml_async(
+        xml: super::xml::GResourceXMLDocument,
+    ) -> GResourceBuilderResult<GResourceBuilder<'static>> {
+        crate::util::spawn_blocking(move || GResourceBuilder::from_xml(xml)).await
+    }
+
     /// Scan a directory and create a GResource file with all the contents of the directory.
     ///
     /// This will ignore any files that end with gresource.xml and meson.build, as
@@ -448,20 +532,28 @@ impl<'a> GResourceBuilder<'a> {
 
     /// Build the binary GResource data
     pub fn build(self) -> GResourceBuilderResult<Vec<u8>> {
-        let builder = GvdbFileWriter::new();
-        let mut table_builder = GvdbHashTableBuilder::new();
+        let table_builder = self.table_builder()?;
+        Ok(GvdbFileWriter::new().write_to_vec_with_table(table_builder)?)
+    }
 
-        for file_data in self.files.into_iter() {
-            let data = GResourceData {
-                size: file_data.size,
-                flags: file_data.flags,
-                data: file_data.data.to_vec(),
-            };
+    /// Write the binary GResource data into the provided [`std::io::Write`]
+    ///
+    /// The file data is copied into `writer` without collecting the whole GResource file in
+    /// memory first. Together with [`GResourceFileData::from_reader`] this allows writing very
+    /// large GResource files. Returns the number of bytes written.
+    pub fn write(self, writer: &mut dyn Write) -> GResourceBuilderResult<usize> {
+        let table_builder = self.table_builder()?;
+        Ok(GvdbFileWriter::new().write_with_table(table_builder, writer)?)
+    }
 
-            table_builder.insert_value(file_data.key(), zvariant::Value::from(data))?;
+    fn table_builder(self) -> GResourceBuilderResult<GvdbHashTableBuilder<'a>> {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        for file_data in self.files.into_iter() {
+            let key = file_data.key.clone();
+            table_builder.insert_stream(&key, file_data.into_stream())?;
         }
 
-        Ok(builder.write_to_vec_with_table(table_builder)?)
+        Ok(table_builder)
     }
 }
 
@@ -469,6 +561,7 @@ impl<'a> GResourceBuilder<'a> {
 mod test {
     use super::*;
     use crate::gresource::xml::GResourceXMLDocument;
+    use crate::gresource::GResourceReader;
     use crate::read::GvdbFile;
     use crate::test::{assert_is_file_3, byte_compare_file_3, GRESOURCE_DIR, GRESOURCE_XML};
     use matches::assert_matches;
@@ -552,6 +645,86 @@ mod test {
         byte_compare_file_3(&root);
     }
 
+    #[test]
+    fn write() {
+        let doc = GResourceXMLDocument::from_file(&GRESOURCE_XML).unwrap();
+        let mut data = Vec::new();
+        let size = GResourceBuilder::from_xml(doc)
+            .unwrap()
+            .write(&mut data)
+            .unwrap();
+        assert_eq!(size, data.len());
+
+        let doc = GResourceXMLDocument::from_file(&GRESOURCE_XML).unwrap();
+        let built = GResourceBuilder::from_xml(doc).unwrap().build().unwrap();
+        assert_eq!(data, built);
+
+        let root = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        byte_compare_file_3(&root);
+    }
+
+    #[test]
+    fn from_reader() {
+        let path = GRESOURCE_DIR.join("json").join("test.json");
+        let reference = std::fs::read(&path).unwrap();
+        let file = std::fs::File::open(&path).unwrap();
+        let file_data =
+            GResourceFileData::from_reader("/test.json".to_string(), file, reference.len() as u32);
+        assert!(format!("{:?}", file_data).contains("Reader"));
+
+        let mut data = Vec::new();
+        GResourceBuilder::from_file_data(vec![file_data])
+            .write(&mut data)
+            .unwrap();
+
+        let root = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let reader = GResourceReader::new(&root).unwrap();
+        assert_eq!(reader.read("/test.json").unwrap(), reference);
+
+        // The reader has less data than announced
+        let file_data = GResourceFileData::from_reader("/test".to_string(), &[1u8, 2][..], 3);
+        let err = GResourceBuilder::from_file_data(vec![file_data])
+            .build()
+            .unwrap_err();
+        assert_matches!(err, GResourceBuilderError::Gvdb(_));
+
+        // The reader has more data than announced
+        let file_data = GResourceFileData::from_reader("/test".to_string(), &b"abcdef"[..], 3);
+        let mut data = Vec::new();
+        GResourceBuilder::from_file_data(vec![file_data])
+            .write(&mut data)
+            .unwrap();
+
+        let root = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let reader = GResourceReader::new(&root).unwrap();
+        assert_eq!(reader.read("/test").unwrap(), b"abc");
+        let table = root.hash_table().unwrap();
+        let info = table.get_item_info("/test").unwrap();
+        assert_eq!(info.value_bytes(), Some(&b"\x03\0\0\0\0\0\0\0abc\0"[..]));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_file_3_async() {
+        let builder = futures_executor::block_on(async {
+            let doc = GResourceXMLDocument::from_file_async(&GRESOURCE_XML)
+                .await
+                .unwrap();
+            GResourceBuilder::from_xml_async(doc).await.unwrap()
+        });
+        let data = builder.build().unwrap();
+        let root = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+
+        assert_is_file_3(&root);
+        byte_compare_file_3(&root);
+
+        let err = futures_executor::block_on(GResourceXMLDocument::from_file_async(
+            &GRESOURCE_DIR.join("missing.gresource.xml"),
+        ))
+        .unwrap_err();
+        assert_matches!(err, crate::gresource::GResourceXMLError::Io(..));
+    }
+
     #[test]
     fn test_file_from_dir() {
         let builder =
@@ -703,7 +876,10 @@ mod test {
         )
         .unwrap();
 
-        let json = std::str::from_utf8(&data.data).unwrap();
+        let GResourceFileContent::Data(data) = data.data else {
+            panic!("Invalid file content");
+        };
+        let json = std::str::from_utf8(&data).unwrap();
         assert_eq!(json, "{\"test\":\"test\"}\n\0");
     }
 
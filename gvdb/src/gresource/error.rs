@@ -89,6 +89,10 @@ pub enum GResourceBuilderError {
 
     /// A generic error with a text description
     Generic(String),
+
+    /// GIO could not load the GResource bundle
+    #[cfg(feature = "gio")]
+    Gio(glib::Error),
 }
 
 impl GResourceBuilderError {
@@ -166,6 +170,10 @@ impl Display for GResourceBuilderError {
             GResourceBuilderError::Generic(err) => {
                 write!(f, "Error while creating GResource file: {}", err)
             }
+            #[cfg(feature = "gio")]
+            GResourceBuilderError::Gio(err) => {
+                write!(f, "Error loading GResource file with GIO: {}", err)
+            }
         }
     }
 }
@@ -0,0 +1,147 @@
+use crate::gresource::{GResourceBuilder, GResourceBuilderError, GResourceBuilderResult};
+
+/// Load a GResource bundle that is embedded in the binary, like the data of the
+/// `include_gresource_from_xml!` and `include_gresource_from_dir!` macros of
+/// [gvdb-macros](https://crates.io/crates/gvdb-macros), and register it globally
+///
+/// The data is not copied if it is aligned to the size of a pointer, which the data of the
+/// macros always is.
+///
+/// ```
+/// # #[repr(align(8))]
+/// # struct Aligned<T: ?Sized>(T);
+/// # static ALIGNED: &Aligned<[u8]> = &Aligned(*include_bytes!(concat!(
+/// #     env!("CARGO_MANIFEST_DIR"),
+/// #     "/test-data/test3.gresource"
+/// # )));
+/// # static GRESOURCE_BYTES: &[u8] = &ALIGNED.0;
+/// // static GRESOURCE_BYTES: &[u8] =
+/// //     gvdb_macros::include_gresource_from_xml!("test-data/gresource/test3.gresource.xml");
+/// let resource = gvdb::gresource::register_static(GRESOURCE_BYTES).unwrap();
+/// let data = gio::resources_lookup_data(
+///     "/gvdb/rs/test/json/test.json",
+///     gio::ResourceLookupFlags::NONE,
+/// )
+/// .unwrap();
+/// assert!(!data.is_empty());
+/// # gio::resources_unregister(&resource);
+/// ```
+pub fn register_static(data: &'static [u8]) -> Result<gio::Resource, glib::Error> {
+    let resource = gio::Resource::from_data(&glib::Bytes::from_static(data))?;
+    gio::resources_register(&resource);
+    Ok(resource)
+}
+
+impl<'a> GResourceBuilder<'a> {
+    /// Build the GResource bundle and load it with GIO, without copying the data
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// use gvdb::gresource::{GResourceBuilder, GResourceXMLDocument};
+    ///
+    /// let doc =
+    ///     GResourceXMLDocument::from_file(&PathBuf::from("test-data/gresource/test3.gresource.xml"))
+    ///         .unwrap();
+    /// let resource = GResourceBuilder::from_xml(doc)
+    ///     .unwrap()
+    ///     .build_gio_resource()
+    ///     .unwrap();
+    /// gio::resources_register(&resource);
+    /// ```
+    pub fn build_gio_resource(self) -> GResourceBuilderResult<gio::Resource> {
+        let data = glib::Bytes::from_owned(self.build()?);
+        gio::Resource::from_data(&data).map_err(GResourceBuilderError::Gio)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::gresource::{GResourceReader, GResourceXMLDocument};
+    use crate::read::{GvdbFile, GvdbHashItemType};
+    use crate::test::GRESOURCE_XML;
+    use std::borrow::Cow;
+
+    const NONE: gio::ResourceLookupFlags = gio::ResourceLookupFlags::NONE;
+
+    #[test]
+    fn gio_reads_all_keys() {
+        let doc = GResourceXMLDocument::from_file(&GRESOURCE_XML).unwrap();
+        let data = GResourceBuilder::from_xml(doc).unwrap().build().unwrap();
+        let resource = gio::Resource::from_data(&glib::Bytes::from_owned(data.clone())).unwrap();
+
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let reader = GResourceReader::new(&file).unwrap();
+        let mut n_files = 0;
+        for entry in file.walk() {
+            let entry = entry.unwrap();
+            match entry.typ() {
+                GvdbHashItemType::Value => {
+                    let bytes = resource.lookup_data(entry.key(), NONE).unwrap();
+                    assert_eq!(&*bytes, &*reader.read(entry.key()).unwrap());
+                    n_files += 1;
+                }
+                GvdbHashItemType::Container => {
+                    let mut children: Vec<String> = resource
+                        .enumerate_children(entry.key(), NONE)
+                        .unwrap()
+                        .into_iter()
+                        .map(String::from)
+                        .collect();
+                    children.sort();
+                    let mut expected = file
+                        .hash_table()
+                        .unwrap()
+                        .keys_with_prefix(entry.key())
+                        .unwrap()
+                        .into_iter()
+                        .filter_map(|key| {
+                            let name = key.strip_prefix(entry.key())?;
+                            let end = name.find('/').map_or(name.len(), |end| end + 1);
+                            (!name.is_empty() && end == name.len()).then(|| name.to_string())
+                        })
+                        .collect::<Vec<_>>();
+                    expected.sort();
+                    assert_eq!(children, expected);
+                }
+                GvdbHashItemType::HashTable => panic!("GResource files contain no hash tables"),
+            }
+        }
+
+        assert_eq!(n_files, 4);
+        assert!(resource.lookup_data("/gvdb/rs/test/missing", NONE).is_err());
+    }
+
+    #[test]
+    fn build_gio_resource() {
+        let doc = GResourceXMLDocument::from_file(&GRESOURCE_XML).unwrap();
+        let resource = GResourceBuilder::from_xml(doc)
+            .unwrap()
+            .build_gio_resource()
+            .unwrap();
+
+        let json = resource
+            .lookup_data("/gvdb/rs/test/json/test.json", NONE)
+            .unwrap();
+        assert!(!json.is_empty());
+    }
+
+    #[test]
+    fn register_static() {
+        // Aligned the same way as the data of the include_gresource macros
+        #[repr(align(16))]
+        struct Aligned<T: ?Sized>(T);
+        static DATA: &Aligned<[u8]> = &Aligned(*include_bytes!("../../test-data/test3.gresource"));
+
+        let resource = super::register_static(&DATA.0).unwrap();
+        let path = "/gvdb/rs/test/online-symbolic.svg";
+        let svg = gio::resources_lookup_data(path, NONE).unwrap();
+        assert_eq!(svg.len(), 1390);
+
+        gio::resources_unregister(&resource);
+        assert!(gio::resources_lookup_data(path, NONE).is_err());
+
+        // Invalid data is rejected
+        assert!(super::register_static(b"invalid").is_err());
+    }
+}
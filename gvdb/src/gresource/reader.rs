@@ -0,0 +1,360 @@
+use crate::gresource::builder::{GResourceData, FLAG_COMPRESSED};
+use crate::read::{GvdbFile, GvdbHashTable, GvdbReaderError, GvdbReaderResult};
+use flate2::read::ZlibDecoder;
+use std::io::Read;
+
+/// Read files from a GResource bundle
+///
+/// Compressed files are decompressed, and the trailing nul byte of uncompressed files is
+/// removed. The size that is stored alongside each file, and its ratio to the size of the
+/// compressed data, are checked against the [`GvdbReaderLimits`](crate::read::GvdbReaderLimits)
+/// of the file before any data is decompressed, and data is never decompressed beyond this size.
+///
+/// ```
+/// use gvdb::gresource::GResourceReader;
+/// use gvdb::read::GvdbFile;
+///
+/// let path = std::path::PathBuf::from("test-data/test3.gresource");
+/// let file = GvdbFile::from_file(&path).unwrap();
+/// let reader = GResourceReader::new(&file).unwrap();
+/// let css = reader.read("/gvdb/rs/test/test.css").unwrap();
+/// assert!(!css.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct GResourceReader<'a> {
+    table: GvdbHashTable<'a>,
+}
+
+impl<'a> GResourceReader<'a> {
+    /// Create a reader for the GResource bundle in `file`
+    pub fn new(file: &'a GvdbFile<'a>) -> GvdbReaderResult<Self> {
+        Ok(Self {
+            table: file.hash_table()?,
+        })
+    }
+
+    /// Read the file at `path` from the GResource bundle
+    pub fn read(&self, path: &str) -> GvdbReaderResult<Vec<u8>> {
+        decode(path, self.resource_data(path)?)
+    }
+
+    /// Like [`read()`](Self::read), but the file is decompressed on a separate thread, so async
+    /// executors are not blocked by large files
+    ///
+    /// Every call spawns a new OS thread. When reading many small files, calling
+    /// [`read()`](Self::read) directly, or from a blocking task of the executor, is cheaper.
+    ///
+    /// ```
+    /// # use gvdb::gresource::GResourceReader;
+    /// # use gvdb::read::GvdbFile;
+    /// # futures_executor::block_on(async {
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = GvdbFile::from_file_async(&path).await.unwrap();
+    /// let reader = GResourceReader::new(&file).unwrap();
+    /// let css = reader.read_async("/gvdb/rs/test/test.css").await.unwrap();
+    /// assert!(!css.is_empty());
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn read_async(&self, path: &str) -> GvdbReaderResult<Vec<u8>> {
+        let data = self.resource_data(path)?;
+        let path = path.to_string();
+        crate::util::spawn_blocking(move || decode(&path, data)).await
+    }
+
+    /// Like [`read()`](Self::read), but the data is returned as [`struct@glib::Bytes`]
+    ///
+    /// The data of uncompressed files is not copied if the GVDB file was created with
+    /// [`GvdbFile::from_glib_bytes`] and has the byte order of the host. The returned bytes
+    /// share the data of the file in this case.
+    ///
+    /// ```
+    /// # use gvdb::gresource::GResourceReader;
+    /// # use gvdb::read::GvdbFile;
+    /// let data = std::fs::read("test-data/test3.gresource").unwrap();
+    /// let file = GvdbFile::from_glib_bytes(glib::Bytes::from_owned(data)).unwrap();
+    /// let reader = GResourceReader::new(&file).unwrap();
+    /// let svg = reader.read_glib_bytes("/gvdb/rs/test/online-symbolic.svg").unwrap();
+    /// assert_eq!(svg.len(), 1390);
+    /// ```
+    #[cfg(feature = "glib")]
+    pub fn read_glib_bytes(&self, path: &str) -> GvdbReaderResult<glib::Bytes> {
+        let type_error = || {
+            GvdbReaderError::DataError(format!(
+                "Unable to parse GResource file '{}': Expected type '(uuay)'",
+                path
+            ))
+        };
+
+        let variant = self
+            .table
+            .get_gvariant(path)?
+            .as_variant()
+            .ok_or_else(type_error)?;
+        if variant.type_().as_str() != "(uuay)" {
+            return Err(type_error());
+        }
+
+        let size = variant.child_value(0).get::<u32>().ok_or_else(type_error)? as usize;
+        let flags = variant.child_value(1).get::<u32>().ok_or_else(type_error)?;
+        self.table.root.limits.check_resource_size(size)?;
+
+        let data = variant.child_value(2).data_as_bytes();
+        if flags & FLAG_COMPRESSED != 0 {
+            self.table
+                .root
+                .limits
+                .check_compression_ratio(size, data.len())?;
+            Ok(glib::Bytes::from_owned(decompress(path, &data, size)?))
+        } else {
+            check_uncompressed(path, &data, size)?;
+            Ok(crate::util::slice_glib_bytes(&data, 0..size))
+        }
+    }
+
+    /// The stored data of the file at `path`, if its size is within the limits of the file
+    fn resource_data(&self, path: &str) -> GvdbReaderResult<GResourceData> {
+        let value = zvariant::OwnedValue::from(self.table.get_value(path)?);
+        let data = GResourceData::try_from(value).map_err(|_| {
+            GvdbReaderError::DataError(format!(
+                "Unable to parse GResource file '{}': Expected type '(uuay)'",
+                path
+            ))
+        })?;
+
+        let limits = &self.table.root.limits;
+        limits.check_resource_size(data.size as usize)?;
+        if data.flags & FLAG_COMPRESSED != 0 {
+            limits.check_compression_ratio(data.size as usize, data.data.len())?;
+        }
+
+        Ok(data)
+    }
+}
+
+/// Decompress the stored data of the GResource file at `path`, or remove the trailing nul byte
+fn decode(path: &str, data: GResourceData) -> GvdbReaderResult<Vec<u8>> {
+    let GResourceData { size, flags, data } = data;
+    let size = size as usize;
+
+    if flags & FLAG_COMPRESSED != 0 {
+        decompress(path, &data, size)
+    } else {
+        check_uncompressed(path, &data, size)?;
+        let mut data = data;
+        data.truncate(size);
+        Ok(data)
+    }
+}
+
+/// Decompress `data`, which has to result in exactly `size` bytes
+fn decompress(path: &str, data: &[u8], size: usize) -> GvdbReaderResult<Vec<u8>> {
+    // Read one more byte than expected to detect data that is too large
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(data)
+        .take(size as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|err| {
+            GvdbReaderError::DataError(format!(
+                "Unable to decompress GResource file '{}': {}",
+                path, err
+            ))
+        })?;
+
+    if decompressed.len() != size {
+        return Err(GvdbReaderError::DataError(format!(
+            "Decompressed size of GResource file '{}' does not match the stored size {}",
+            path, size
+        )));
+    }
+
+    Ok(decompressed)
+}
+
+/// Check that uncompressed `data` consists of `size` bytes followed by a nul byte
+fn check_uncompressed(path: &str, data: &[u8], size: usize) -> GvdbReaderResult<()> {
+    if data.len() != size + 1 || data[size] != 0 {
+        return Err(GvdbReaderError::DataError(format!(
+            "Size of GResource file '{}' does not match the stored size {}",
+            path, size
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::GvdbReaderLimits;
+    use crate::test::{GRESOURCE_DIR, TEST_FILE_3};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use matches::assert_matches;
+    use std::borrow::Cow;
+
+    #[test]
+    fn read() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let reader = GResourceReader::new(&file).unwrap();
+
+        let svg = reader
+            .read("/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg")
+            .unwrap();
+        let reference =
+            std::fs::read(GRESOURCE_DIR.join("icons/scalable/actions/send-symbolic.svg")).unwrap();
+        assert_eq!(svg, reference);
+
+        let svg = reader.read("/gvdb/rs/test/online-symbolic.svg").unwrap();
+        assert_eq!(svg.len(), 1390);
+
+        let err = reader.read("/gvdb/rs/test/").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataError(_));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn read_async() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let reader = GResourceReader::new(&file).unwrap();
+
+        let svg = futures_executor::block_on(
+            reader.read_async("/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg"),
+        )
+        .unwrap();
+        let reference =
+            std::fs::read(GRESOURCE_DIR.join("icons/scalable/actions/send-symbolic.svg")).unwrap();
+        assert_eq!(svg, reference);
+
+        let err = futures_executor::block_on(reader.read_async("/gvdb/rs/test/")).unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataError(_));
+    }
+
+    #[test]
+    fn limit() {
+        let mut file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        file.set_limits(GvdbReaderLimits {
+            max_resource_size: 1000,
+            ..Default::default()
+        });
+
+        let reader = GResourceReader::new(&file).unwrap();
+        assert!(reader.read("/gvdb/rs/test/json/test.json").is_ok());
+        let err = reader
+            .read("/gvdb/rs/test/online-symbolic.svg")
+            .unwrap_err();
+        assert_matches!(err, GvdbReaderError::LimitExceeded(_));
+    }
+
+    #[test]
+    fn compression_ratio() {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        std::io::Write::write_all(&mut encoder, &[0; 1 << 20]).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let ratio = (1 << 20) / compressed.len();
+
+        let mut table = GvdbHashTableBuilder::new();
+        table
+            .insert_value(
+                "/zeros",
+                zvariant::Value::from(GResourceData {
+                    size: 1 << 20,
+                    flags: FLAG_COMPRESSED,
+                    data: compressed,
+                }),
+            )
+            .unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table)
+            .unwrap();
+
+        let mut file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        assert_eq!(
+            GResourceReader::new(&file).unwrap().read("/zeros").unwrap(),
+            vec![0; 1 << 20]
+        );
+
+        file.set_limits(GvdbReaderLimits {
+            max_compression_ratio: ratio - 1,
+            ..Default::default()
+        });
+        let err = GResourceReader::new(&file)
+            .unwrap()
+            .read("/zeros")
+            .unwrap_err();
+        assert_matches!(err, GvdbReaderError::LimitExceeded(_));
+    }
+
+    #[test]
+    fn size_mismatch() {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, &[1; 4096]).unwrap();
+
+        // Pretend the compressed data is smaller than it is
+        let compressed = GResourceData {
+            size: 10,
+            flags: FLAG_COMPRESSED,
+            data: encoder.finish().unwrap(),
+        };
+        let uncompressed = GResourceData {
+            size: 10,
+            flags: 0,
+            data: vec![1; 4],
+        };
+
+        let mut table = GvdbHashTableBuilder::new();
+        table
+            .insert_value("/compressed", zvariant::Value::from(compressed))
+            .unwrap();
+        table
+            .insert_value("/uncompressed", zvariant::Value::from(uncompressed))
+            .unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table)
+            .unwrap();
+
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let reader = GResourceReader::new(&file).unwrap();
+        let err = reader.read("/compressed").unwrap_err();
+        assert_matches!(err, GvdbReaderError::DataError(_));
+        let err = reader.read("/uncompressed").unwrap_err();
+        assert_matches!(err, GvdbReaderError::DataError(_));
+    }
+}
+
+#[cfg(all(feature = "glib", test))]
+mod test_glib {
+    use super::*;
+    use crate::test::{GRESOURCE_DIR, TEST_FILE_3};
+
+    #[test]
+    fn read_glib_bytes() {
+        let data = glib::Bytes::from_owned(std::fs::read(&*TEST_FILE_3).unwrap());
+        let file = GvdbFile::from_glib_bytes(data.clone()).unwrap();
+        let reader = GResourceReader::new(&file).unwrap();
+
+        for path in [
+            "icons/scalable/actions/send-symbolic.svg",
+            "online-symbolic.svg",
+            "test.css",
+        ] {
+            let key = format!("/gvdb/rs/test/{}", path);
+            let bytes = reader.read_glib_bytes(&key).unwrap();
+            assert_eq!(&*bytes, &*reader.read(&key).unwrap());
+        }
+
+        // Uncompressed files share the data of the GVDB file
+        let svg = reader
+            .read_glib_bytes("/gvdb/rs/test/online-symbolic.svg")
+            .unwrap();
+        let start = svg.as_ptr() as usize - data.as_ptr() as usize;
+        assert!(start + svg.len() <= data.len());
+        let reference =
+            std::fs::read(GRESOURCE_DIR.join("icons/scalable/actions/online-symbolic.svg"))
+                .unwrap();
+        assert_eq!(&*svg, &reference[..]);
+
+        let err = reader.read_glib_bytes("/gvdb/rs/test/").unwrap_err();
+        assert!(matches!(err, GvdbReaderError::DataError(_)));
+    }
+}
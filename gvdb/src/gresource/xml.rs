@@ -141,6 +141,24 @@ where
 impl GResourceXMLDocument {
     /// Load a GResource XML file from disk using `path`
     pub fn from_file(path: &Path) -> GResourceXMLResult<Self> {
+        let data = Self::read_file(path)?;
+        let dir = path.parent().unwrap();
+        Self::from_bytes_with_filename(dir, Some(path.to_path_buf()), Cow::Owned(data))
+    }
+
+    /// Like [`from_file()`](Self::from_file), but the file is read on a separate thread, so
+    /// async executors are not blocked
+    ///
+    /// A new OS thread is spawned for every call.
+    #[cfg(feature = "async")]
+    pub async fn from_file_async(path: &Path) -> GResourceXMLResult<Self> {
+        let filename = path.to_path_buf();
+        let data = crate::util::spawn_blocking(move || Self::read_file(&filename)).await?;
+        let dir = path.parent().unwrap();
+        Self::from_bytes_with_filename(dir, Some(path.to_path_buf()), Cow::Owned(data))
+    }
+
+    fn read_file(path: &Path) -> GResourceXMLResult<Vec<u8>> {
         let mut file =
             std::fs::File::open(path).map_err(GResourceXMLError::from_io_with_filename(path))?;
         let mut data = Vec::with_capacity(
@@ -150,9 +168,7 @@ impl GResourceXMLDocument {
         );
         file.read_to_end(&mut data)
             .map_err(GResourceXMLError::from_io_with_filename(path))?;
-
-        let dir = path.parent().unwrap();
-        Self::from_bytes_with_filename(dir, Some(path.to_path_buf()), Cow::Owned(data))
+        Ok(data)
     }
 
     /// Load a GResource XML file from the provided `Cow<[u8]>` bytes. A filename is provided for
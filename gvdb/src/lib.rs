@@ -62,30 +62,71 @@
 //!
 //! ## Features
 //!
-//! By default, no features are enabled.
+//! By default, only the `std` feature is enabled.
+//!
+//! ### `std`
+//!
+//! Enabled by default. Provides decoding values with zvariant, reading files from disk, the
+//! writer and everything that builds on them. Without it, the crate is `no_std` and only contains
+//! a reader that needs `alloc`. It reads GVDB files from byte slices and gives access to hash
+//! tables and the raw bytes of items with
+//! [`get_item_info`](crate::read::GvdbHashTable::get_item_info). All other features imply `std`.
 //!
 //! ### `mmap`
 //!
 //! Use the memmap2 crate to read memory-mapped GVDB files.
 //!
+//! ### `memfd`
+//!
+//! Write GVDB files to sealed memfds and map them into memory without `unsafe` code, with
+//! [`GvdbFile::from_sealed_memfd`](crate::read::GvdbFile::from_sealed_memfd) and
+//! [`GvdbFileWriter::write_to_sealed_memfd`](crate::write::GvdbFileWriter::write_to_sealed_memfd).
+//! Only available on Linux.
+//!
+//! ### `reload`
+//!
+//! Provides [`ReloadingGvdbFile`](crate::read::ReloadingGvdbFile), which reloads a GVDB file
+//! when it is replaced on disk. On Linux the file is watched with inotify.
+//!
 //! ### `glib`
 //!
 //! By default this crate uses the [glib](https://crates.io/crates/zvariant) crate to allow reading
 //! and writing `GVariant` data to the gvdb files. By enabling this feature you can pass GVariants
 //! directly from the glib crate as well.
 //!
+//! Files can be read from [`glib::Bytes`](struct@glib::Bytes) without copying with
+//! [`GvdbFile::from_glib_bytes`](crate::read::GvdbFile::from_glib_bytes), and
+//! [`get_as_variant`](crate::read::GvdbHashTable::get_as_variant) converts values with
+//! `glib::FromVariant`. The [`variant`] module converts between `zvariant::Value` and
+//! `glib::Variant`.
+//!
 //! ### `gresource`
 //!
 //! To be able to compile GResource files, the `gresource` feature must be enabled.
 //!
+//! ### `async`
+//!
+//! Adds async variants of the functions that read or compress files, like
+//! [`GvdbFile::from_file_async`](crate::read::GvdbFile::from_file_async). They do the work on a
+//! new thread for each call and work with any executor.
+//!
+//! ### `gio`
+//!
+//! Load GResource bundles as [`gio::Resource`], for example to register them for GTK
+//! applications. See [`register_static`](crate::gresource::register_static) and
+//! [`build_gio_resource`](crate::gresource::GResourceBuilder::build_gio_resource). Implies
+//! `gresource` and `glib`.
+//!
 //! ## Macros
 //!
 //! The [gvdb-macros](https://crates.io/crates/gvdb-macros) crate provides useful macros for
 //! GResource file creation.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+extern crate alloc;
 extern crate core;
 
 /// Read GResource XML files and compile a GResource file
@@ -104,9 +145,16 @@ pub mod read;
 /// Create GVDB files
 ///
 /// See the documentation of [`GvdbFileWriter`](crate::write::GvdbFileWriter) to get started
+#[cfg(feature = "std")]
 pub mod write;
 
-#[cfg(test)]
+/// Convert between [`enum@zvariant::Value`] and [`struct@glib::Variant`]
+///
+/// Requires the `glib` feature.
+#[cfg(feature = "glib")]
+pub mod variant;
+
+#[cfg(all(test, feature = "std"))]
 pub(crate) mod test;
 
 mod util;
@@ -1,15 +1,54 @@
+#[cfg(feature = "std")]
+mod dump;
 mod error;
 mod file;
 mod hash;
 mod hash_item;
 mod header;
+mod key;
+#[cfg(feature = "std")]
+mod lazy;
+mod limits;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "std")]
+mod path;
 mod pointer;
+#[cfg(feature = "std")]
+mod query;
+#[cfg(feature = "reload")]
+mod reload;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(feature = "std")]
+mod stats;
+mod walk;
 
-pub use error::{GvdbReaderError, GvdbReaderResult};
-pub use file::GvdbFile;
+pub use error::{GvdbErrorContext, GvdbReaderError, GvdbReaderResult};
+pub use file::{GvdbFile, GvdbStorage};
 pub use hash::GvdbHashTable;
+pub use hash_item::{GvdbHashItemType, GvdbItemInfo};
+pub use key::GvdbKey;
+#[cfg(feature = "std")]
+pub use lazy::{LazyGvdbFile, LazyHashTable};
+pub use limits::GvdbReaderLimits;
+#[cfg(feature = "mmap")]
+pub use mmap::{GvdbMmapAdvice, GvdbMmapOptions};
+#[cfg(feature = "std")]
+pub use path::GvdbPathItem;
+#[cfg(feature = "reload")]
+pub use reload::ReloadingGvdbFile;
+#[cfg(feature = "std")]
+pub use shared::{OwnedHashTable, SharedGvdbFile};
+#[cfg(feature = "std")]
+pub use stats::{GvdbFileStats, GvdbTableStats};
+pub use walk::{GvdbWalk, GvdbWalkEntry};
 
+#[cfg(feature = "std")]
 pub(crate) use hash::GvdbHashHeader;
-pub(crate) use hash_item::{GvdbHashItem, GvdbHashItemType};
+#[cfg(feature = "std")]
+pub(crate) use hash_item::GvdbHashItem;
+#[cfg(feature = "std")]
 pub(crate) use header::GvdbHeader;
+#[cfg(feature = "std")]
 pub(crate) use pointer::GvdbPointer;
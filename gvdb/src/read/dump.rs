@@ -0,0 +1,411 @@
+use crate::read::error::GvdbReaderResult;
+use crate::read::file::GvdbFile;
+use crate::read::hash::GvdbHashTable;
+use crate::read::hash_item::{GvdbHashItem, GvdbHashItemType, GvdbItemInfo};
+use crate::read::header::GvdbHeader;
+use crate::read::pointer::GvdbPointer;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::mem::size_of;
+
+const ROW_WIDTH: usize = 16;
+
+/// An annotated range of the file
+struct DumpEntry {
+    start: usize,
+    end: usize,
+    title: String,
+    details: Vec<String>,
+}
+
+#[derive(Default)]
+struct Dumper {
+    entries: Vec<DumpEntry>,
+    visited: HashSet<usize>,
+}
+
+fn format_pointer(pointer: &GvdbPointer) -> String {
+    format!("{:08x}..{:08x}", pointer.start(), pointer.end())
+}
+
+fn format_path(path: &[String]) -> String {
+    if path.is_empty() {
+        "root".to_string()
+    } else {
+        format!("{:?}", path)
+    }
+}
+
+/// Write `data`, which starts at `offset` in the file, as rows of hex bytes and ASCII characters
+fn write_hex(out: &mut String, offset: usize, data: &[u8]) {
+    for (row, bytes) in data.chunks(ROW_WIDTH).enumerate() {
+        let _ = write!(out, "    {:08x} ", offset + row * ROW_WIDTH);
+        for column in 0..ROW_WIDTH {
+            match bytes.get(column) {
+                Some(byte) => {
+                    let _ = write!(out, " {:02x}", byte);
+                }
+                None => out.push_str("   "),
+            }
+        }
+
+        out.push_str("  |");
+        for byte in bytes {
+            out.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            });
+        }
+        out.push_str("|\n");
+    }
+}
+
+impl Dumper {
+    fn add(&mut self, pointer: &GvdbPointer, title: String, details: Vec<String>) {
+        self.entries.push(DumpEntry {
+            start: pointer.start() as usize,
+            end: pointer.end() as usize,
+            title,
+            details,
+        });
+    }
+
+    fn add_header(&mut self, file: &GvdbFile) -> GvdbReaderResult<GvdbHeader> {
+        let header = file.get_header()?;
+        let endianness = if file.byteswapped == cfg!(target_endian = "little") {
+            "big endian"
+        } else {
+            "little endian"
+        };
+
+        self.add(
+            &GvdbPointer::new(0, size_of::<GvdbHeader>()),
+            "file header".to_string(),
+            vec![
+                format!("signature: GVariant ({})", endianness),
+                format!("version: {}", header.version()),
+                format!("root: {}", format_pointer(header.root())),
+            ],
+        );
+
+        Ok(header)
+    }
+
+    fn add_table(&mut self, file: &GvdbFile, pointer: &GvdbPointer, path: Vec<String>) {
+        if !self.visited.insert(pointer.start() as usize) {
+            return;
+        }
+
+        let mut sub_tables = Vec::new();
+        let result = file
+            .limits
+            .check_depth(path.len())
+            .and_then(|_| self.add_table_items(file, pointer, &path, &mut sub_tables));
+        if let Err(err) = result {
+            self.add(
+                pointer,
+                format!("hash table {} (invalid)", format_path(&path)),
+                vec![format!("error: {}", err)],
+            );
+        }
+
+        for (sub_pointer, sub_path) in sub_tables {
+            self.add_table(file, &sub_pointer, sub_path);
+        }
+    }
+
+    fn add_table_items(
+        &mut self,
+        file: &GvdbFile,
+        pointer: &GvdbPointer,
+        path: &[String],
+        sub_tables: &mut Vec<(GvdbPointer, Vec<String>)>,
+    ) -> GvdbReaderResult<()> {
+        let table = GvdbHashTable::for_bytes(file.dereference(pointer, 4)?, file)?;
+        let data = &table.table;
+        let header = data.header();
+        let start = pointer.start() as usize;
+
+        let mut details = vec![format!(
+            "{:08x} header: n_bloom_words: {}, bloom_shift: {}, n_buckets: {}",
+            start,
+            header.n_bloom_words(),
+            header.bloom_shift(),
+            header.n_buckets()
+        )];
+
+        let bloom_words = data.bloom_words().unwrap_or_default();
+        if !bloom_words.is_empty() {
+            let words: Vec<String> = bloom_words.iter().map(|w| format!("{:08x}", w)).collect();
+            details.push(format!(
+                "{:08x} bloom words: [{}]",
+                start + data.bloom_words_offset(),
+                words.join(", ")
+            ));
+        }
+
+        let buckets = (0..header.n_buckets() as usize)
+            .map(|bucket| data.get_hash(bucket).map(|index| index.to_string()))
+            .collect::<GvdbReaderResult<Vec<String>>>()?;
+        details.push(format!(
+            "{:08x} buckets (first item index): [{}]",
+            start + data.hash_buckets_offset(),
+            buckets.join(", ")
+        ));
+
+        let names = table.get_names();
+        for index in 0..data.n_hash_items() {
+            let item = data.get_hash_item_for_index(index)?;
+            let item_offset = start + data.hash_items_offset() + index * size_of::<GvdbHashItem>();
+            let key = file
+                .dereference(&item.key_ptr(), 1)
+                .map(|key| String::from_utf8_lossy(key).to_string())
+                .unwrap_or_else(|err| format!("<{}>", err));
+            let full_key = names
+                .as_ref()
+                .ok()
+                .and_then(|names| names.get(index).cloned())
+                .unwrap_or_else(|| key.clone());
+            let parent = if item.parent() == u32::MAX {
+                "none".to_string()
+            } else {
+                item.parent().to_string()
+            };
+            let typ = item.typ();
+            let typ_char = typ
+                .as_ref()
+                .map(|typ| (u8::from(*typ) as char).to_string())
+                .unwrap_or_else(|_| "?".to_string());
+
+            details.push(format!(
+                "{:08x} item {}: hash: {:08x}, parent: {}, key: {} {:?}, type: '{}', value: {}",
+                item_offset,
+                index,
+                item.hash_value(),
+                parent,
+                format_pointer(&item.key_ptr()),
+                key,
+                typ_char,
+                format_pointer(item.value_ptr())
+            ));
+
+            self.add(
+                &item.key_ptr(),
+                format!("key of item {} in hash table {}", index, format_path(path)),
+                vec![format!("{:?}", key)],
+            );
+
+            let value_ptr = item.value_ptr();
+            match typ {
+                Ok(GvdbHashItemType::Value) => {
+                    let mut value_details = Vec::new();
+                    if let Ok(data) = file.dereference(value_ptr, 8) {
+                        let info = GvdbItemInfo::new(
+                            full_key.clone(),
+                            GvdbHashItemType::Value,
+                            data,
+                            file.byteswapped,
+                        );
+                        value_details.push(format!(
+                            "signature: {}",
+                            info.signature().unwrap_or("<invalid>")
+                        ));
+                    }
+                    self.add(value_ptr, format!("value {:?}", full_key), value_details);
+                }
+                Ok(GvdbHashItemType::HashTable) => {
+                    let mut sub_path = path.to_vec();
+                    sub_path.push(full_key);
+                    sub_tables.push((*value_ptr, sub_path));
+                }
+                Ok(GvdbHashItemType::Container) => {
+                    let children = file
+                        .dereference(value_ptr, 4)
+                        .map(|data| {
+                            data.chunks_exact(4)
+                                .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]).to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                        .unwrap_or_else(|err| format!("<{}>", err));
+                    self.add(
+                        value_ptr,
+                        format!("children of container {:?}", full_key),
+                        vec![format!("item indices: [{}]", children)],
+                    );
+                }
+                Err(_) => (),
+            }
+        }
+
+        self.add(
+            pointer,
+            format!("hash table {}", format_path(path)),
+            details,
+        );
+
+        Ok(())
+    }
+
+    fn write(mut self, file: &GvdbFile) -> String {
+        let data = file.data.as_ref();
+        self.entries.sort_by_key(|entry| (entry.start, entry.end));
+
+        let mut out = String::new();
+        let mut covered_end = 0;
+        for entry in &self.entries {
+            if entry.start > covered_end {
+                let gap = &data[covered_end.min(data.len())..entry.start.min(data.len())];
+                let kind = if gap.len() < 8 && gap.iter().all(|b| *b == 0) {
+                    "padding"
+                } else {
+                    "unreferenced"
+                };
+                let _ = writeln!(
+                    out,
+                    "{:08x}..{:08x} {} ({} bytes)",
+                    covered_end,
+                    entry.start,
+                    kind,
+                    gap.len()
+                );
+                write_hex(&mut out, covered_end, gap);
+            } else if entry.start < covered_end {
+                let _ = writeln!(out, "warning: the next entry overlaps the previous one");
+            }
+
+            let _ = writeln!(
+                out,
+                "{:08x}..{:08x} {} ({} bytes)",
+                entry.start,
+                entry.end,
+                entry.title,
+                entry.end.saturating_sub(entry.start)
+            );
+            for detail in &entry.details {
+                let _ = writeln!(out, "  {}", detail);
+            }
+
+            match data.get(entry.start..entry.end) {
+                Some(bytes) => write_hex(&mut out, entry.start, bytes),
+                None => {
+                    let _ = writeln!(out, "  error: range is outside of the file");
+                }
+            }
+
+            covered_end = covered_end.max(entry.end);
+        }
+
+        if covered_end < data.len() {
+            let _ = writeln!(
+                out,
+                "{:08x}..{:08x} unreferenced ({} bytes)",
+                covered_end,
+                data.len(),
+                data.len() - covered_end
+            );
+            write_hex(&mut out, covered_end, &data[covered_end..]);
+        }
+
+        out
+    }
+}
+
+impl<'a> GvdbFile<'a> {
+    /// Create an annotated map of the file contents for debugging
+    ///
+    /// Every byte of the file is listed as hex, together with the structure it belongs to: the
+    /// file header, hash table headers, bloom words, buckets and items with their decoded fields,
+    /// keys, values and container child lists. Alignment padding and data that isn't referenced
+    /// from anywhere is marked as well.
+    ///
+    /// Invalid hash tables are annotated with the error instead of failing the dump.
+    ///
+    /// ```
+    /// # use gvdb::read::GvdbFile;
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let dump = file.dump();
+    /// assert!(dump.contains("file header"));
+    /// println!("{}", dump);
+    /// ```
+    pub fn dump(&self) -> String {
+        let mut dumper = Dumper::default();
+        // The header is checked when creating the file
+        if let Ok(header) = dumper.add_header(self) {
+            dumper.add_table(self, header.root(), Vec::new());
+        }
+
+        dumper.write(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::GvdbFile;
+    use crate::test::*;
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_file_2() {
+        let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
+        let dump = file.dump();
+        println!("{}", dump);
+
+        assert!(dump.starts_with("00000000..00000018 file header (24 bytes)\n"));
+        assert!(dump.contains("signature: GVariant (big endian)"));
+        assert!(dump.contains("hash table root"));
+        assert!(dump.contains("hash table [\"table\"]"));
+        assert!(dump.contains("value \"string\""));
+        assert!(dump.contains("signature: s"));
+        assert!(dump.contains("|raVGtnai"));
+        assert!(!dump.contains("unreferenced"));
+    }
+
+    #[test]
+    fn test_file_3() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let dump = file.dump();
+        assert!(dump.contains("children of container \"/gvdb/rs/test/\""));
+        assert!(dump.contains("signature: (uuay)"));
+        assert!(dump.contains("padding"));
+        assert!(!dump.contains("unreferenced"));
+    }
+
+    #[test]
+    fn unreferenced() {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.insert_string("test", "test").unwrap();
+        table_builder.set_bloom_filter(1, 5).unwrap();
+        let mut data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        data.extend_from_slice(b"junk");
+
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let dump = file.dump();
+        assert!(dump.contains("bloom words: ["));
+        assert!(dump.contains("unreferenced (4 bytes)"));
+        assert!(dump.contains("|junk|"));
+    }
+
+    #[test]
+    fn invalid_table() {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.insert_string("test", "test").unwrap();
+        let mut data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        // Point the root table outside of the file
+        data[16..20].copy_from_slice(&1000u32.to_le_bytes());
+        data[20..24].copy_from_slice(&2000u32.to_le_bytes());
+
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let dump = file.dump();
+        assert!(dump.contains("hash table root (invalid)"));
+        assert!(dump.contains("error: range is outside of the file"));
+        assert!(dump.contains("unreferenced"));
+    }
+}
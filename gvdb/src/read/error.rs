@@ -1,19 +1,111 @@
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{FromUtf8Error, String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+use core::num::TryFromIntError;
+use core::ops::Range;
+#[cfg(feature = "std")]
 use std::error::Error;
-use std::fmt::{Display, Formatter};
-use std::num::TryFromIntError;
+#[cfg(feature = "std")]
 use std::path::{Path, PathBuf};
-use std::string::FromUtf8Error;
+
+/// Information about where in a GVDB file an error occurred
+///
+/// All parts are optional, as not every error can be attributed to a location. The context of an
+/// error is returned by [`GvdbReaderError::context()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GvdbErrorContext {
+    offset: Option<usize>,
+    range: Option<Range<usize>>,
+    alignment: Option<usize>,
+    table_path: Vec<String>,
+    item_index: Option<usize>,
+}
+
+impl GvdbErrorContext {
+    /// The byte offset in the file at which the error occurred
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// The byte range in the file that was accessed
+    pub fn range(&self) -> Option<Range<usize>> {
+        self.range.clone()
+    }
+
+    /// The alignment the accessed data was expected to have
+    pub fn alignment(&self) -> Option<usize> {
+        self.alignment
+    }
+
+    /// The keys of the items that lead from the root hash table to the hash table in which the
+    /// error occurred. Empty for the root table.
+    pub fn table_path(&self) -> &[String] {
+        &self.table_path
+    }
+
+    /// The index of the hash item in its hash table
+    pub fn item_index(&self) -> Option<usize> {
+        self.item_index
+    }
+
+    /// Fill in the parts of the context that are not known yet from `other`
+    fn merge(&mut self, other: GvdbErrorContext) {
+        if self.offset.is_none() {
+            self.offset = other.offset;
+        }
+        if self.range.is_none() {
+            self.range = other.range;
+        }
+        if self.alignment.is_none() {
+            self.alignment = other.alignment;
+        }
+        if self.table_path.is_empty() {
+            self.table_path = other.table_path;
+        }
+        if self.item_index.is_none() {
+            self.item_index = other.item_index;
+        }
+    }
+}
+
+impl Display for GvdbErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(offset) = self.offset {
+            parts.push(format!("offset {}", offset));
+        }
+        if let Some(range) = &self.range {
+            parts.push(format!("range {}..{}", range.start, range.end));
+        }
+        if let Some(alignment) = self.alignment {
+            parts.push(format!("alignment {}", alignment));
+        }
+        if !self.table_path.is_empty() {
+            parts.push(format!("hash table {:?}", self.table_path));
+        }
+        if let Some(item_index) = self.item_index {
+            parts.push(format!("item {}", item_index));
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
 
 /// An error that can occur during GVDB file reading
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum GvdbReaderError {
     /// Error converting a string to UTF-8
     Utf8(FromUtf8Error),
 
     /// Generic I/O error. Path contains an optional filename if applicable
+    #[cfg(feature = "std")]
     Io(std::io::Error, Option<PathBuf>),
 
     /// An error occured when deserializing variant data with zvariant
+    #[cfg(feature = "std")]
     ZVariant(zvariant::Error),
 
     /// Tried to access an invalid data offset
@@ -30,18 +122,109 @@ pub enum GvdbReaderError {
 
     /// The item with the specified key does not exist in the hash table
     KeyError(String),
+
+    /// The file exceeds one of the configured [`GvdbReaderLimits`](crate::read::GvdbReaderLimits)
+    LimitExceeded(String),
+
+    /// Looking up a path failed at the segment with the index `segment`
+    Path {
+        /// The segments of the path
+        path: Vec<String>,
+
+        /// The index of the segment that failed
+        segment: usize,
+
+        /// The error that occurred when looking up the segment
+        error: Box<GvdbReaderError>,
+    },
+
+    /// The error `error` occurred at the location in the file described by `context`
+    ///
+    /// Use [`inner()`](Self::inner) to match the error regardless of its context.
+    Context {
+        /// The error that occurred
+        error: Box<GvdbReaderError>,
+
+        /// Where in the file the error occurred
+        context: GvdbErrorContext,
+    },
 }
 
 impl GvdbReaderError {
+    #[cfg(feature = "std")]
     pub(crate) fn from_io_with_filename(
         filename: &Path,
     ) -> impl FnOnce(std::io::Error) -> GvdbReaderError {
         let path = filename.to_path_buf();
         move |err| GvdbReaderError::Io(err, Some(path))
     }
+
+    /// The error without the context that was added to it
+    pub fn inner(&self) -> &GvdbReaderError {
+        match self {
+            GvdbReaderError::Context { error, .. } => error.inner(),
+            error => error,
+        }
+    }
+
+    /// Information about where in the file the error occurred, if it is known
+    pub fn context(&self) -> Option<&GvdbErrorContext> {
+        match self {
+            GvdbReaderError::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Add information about where in the file the error occurred. The first context that is
+    /// added comes from the most specific location, so parts of it are never replaced.
+    pub(crate) fn with_context(self, f: impl FnOnce(&mut GvdbErrorContext)) -> Self {
+        let mut new_context = GvdbErrorContext::default();
+        f(&mut new_context);
+        if new_context == GvdbErrorContext::default() {
+            return self;
+        }
+
+        match self {
+            GvdbReaderError::Context { error, mut context } => {
+                context.merge(new_context);
+                GvdbReaderError::Context { error, context }
+            }
+            error => GvdbReaderError::Context {
+                error: Box::new(error),
+                context: new_context,
+            },
+        }
+    }
+
+    /// Add the location of accessed data to the context of this error
+    pub(crate) fn with_range(self, start: usize, end: usize, alignment: usize) -> Self {
+        self.with_context(|context| {
+            context.offset = Some(start);
+            context.range = Some(start..end);
+            context.alignment = Some(alignment);
+        })
+    }
+
+    /// Add the hash table path and the item index to the context of this error
+    pub(crate) fn with_item(self, table_path: &[String], item_index: Option<usize>) -> Self {
+        self.with_context(|context| {
+            context.table_path = table_path.to_vec();
+            context.item_index = item_index;
+        })
+    }
 }
 
-impl Error for GvdbReaderError {}
+#[cfg(feature = "std")]
+impl Error for GvdbReaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GvdbReaderError::Path { error, .. } | GvdbReaderError::Context { error, .. } => {
+                Some(error.as_ref())
+            }
+            _ => None,
+        }
+    }
+}
 
 impl From<FromUtf8Error> for GvdbReaderError {
     fn from(err: FromUtf8Error) -> Self {
@@ -49,6 +232,7 @@ impl From<FromUtf8Error> for GvdbReaderError {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<zvariant::Error> for GvdbReaderError {
     fn from(err: zvariant::Error) -> Self {
         Self::ZVariant(err)
@@ -86,9 +270,10 @@ impl<S, T> From<safe_transmute::Error<'_, S, T>> for GvdbReaderError {
 }
 
 impl Display for GvdbReaderError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             GvdbReaderError::Utf8(err) => write!(f, "Error converting string to UTF-8: {}", err),
+            #[cfg(feature = "std")]
             GvdbReaderError::Io(err, path) => {
                 if let Some(path) = path {
                     write!(
@@ -101,6 +286,7 @@ impl Display for GvdbReaderError {
                     write!(f, "I/O error: {}", err)
                 }
             }
+            #[cfg(feature = "std")]
             GvdbReaderError::ZVariant(err) => write!(f, "Error parsing ZVariant data: {}", err),
             GvdbReaderError::DataOffset => {
                 write!(f, "Tried to access an invalid data offset. Most likely reason is a corrupted GVDB file")
@@ -127,6 +313,26 @@ impl Display for GvdbReaderError {
             GvdbReaderError::KeyError(key) => {
                 write!(f, "The item with the key '{}' does not exist", key)
             }
+            GvdbReaderError::LimitExceeded(msg) => {
+                write!(f, "Reader limit exceeded: {}", msg)
+            }
+            GvdbReaderError::Path {
+                path,
+                segment,
+                error,
+            } => {
+                write!(
+                    f,
+                    "Error looking up segment {} ('{}') of path {:?}: {}",
+                    segment,
+                    path.get(*segment).map(String::as_str).unwrap_or_default(),
+                    path,
+                    error
+                )
+            }
+            GvdbReaderError::Context { error, context } => {
+                write!(f, "{} (at {})", error, context)
+            }
         }
     }
 }
@@ -134,7 +340,7 @@ impl Display for GvdbReaderError {
 /// The Result type for [`GvdbReaderError`]
 pub type GvdbReaderResult<T> = Result<T, GvdbReaderError>;
 
-#[cfg(test)]
+#[cfg(all(feature = "std", test))]
 mod test {
     use crate::read::{GvdbHeader, GvdbPointer, GvdbReaderError};
     use matches::assert_matches;
@@ -169,6 +375,52 @@ mod test {
         let err = GvdbReaderError::KeyError("test".to_string());
         assert!(format!("{}", err).contains("test"));
 
+        let err = GvdbReaderError::LimitExceeded("too deep".to_string());
+        assert!(format!("{}", err).contains("limit exceeded: too deep"));
+
+        let err = GvdbReaderError::Path {
+            path: vec!["a".to_string(), "b".to_string()],
+            segment: 1,
+            error: Box::new(err),
+        };
+        assert!(format!("{}", err).contains("segment 1 ('b') of path [\"a\", \"b\"]"));
+        assert!(std::error::Error::source(&err).is_some());
+
+        // Context is added to all errors, the inner error stays matchable
+        let err = GvdbReaderError::DataOffset.with_range(8, 16, 4);
+        assert_matches!(err.inner(), GvdbReaderError::DataOffset);
+        let context = err.context().unwrap();
+        assert_eq!(context.offset(), Some(8));
+        assert_eq!(context.range(), Some(8..16));
+        assert_eq!(context.alignment(), Some(4));
+        assert!(format!("{}", err).contains("data offset"));
+        assert!(format!("{}", err).contains("(at offset 8, range 8..16, alignment 4)"));
+
+        let err = GvdbReaderError::KeyError("key".to_string()).with_item(&[], Some(3));
+        assert_matches!(err.inner(), GvdbReaderError::KeyError(key) if key == "key");
+        assert_eq!(err.context().unwrap().item_index(), Some(3));
+
+        let err = GvdbReaderError::DataError("invalid (at offset 1)".to_string())
+            .with_range(8, 16, 4)
+            .with_item(&["table".to_string()], Some(3));
+        assert_matches!(err.inner(), GvdbReaderError::DataError(msg) if msg == "invalid (at offset 1)");
+        let context = err.context().unwrap();
+        assert_eq!(context.offset(), Some(8));
+        assert_eq!(context.table_path(), ["table"]);
+        assert_eq!(context.item_index(), Some(3));
+        assert!(format!("{}", err).contains(
+            "invalid (at offset 1) (at offset 8, range 8..16, alignment 4, hash table [\"table\"], item 3)"
+        ));
+        assert!(std::error::Error::source(&err).is_some());
+
+        // Existing context is not replaced, and empty context is not added
+        let err = err.with_item(&[], Some(5));
+        assert_eq!(err.context().unwrap().item_index(), Some(3));
+        assert_matches!(err, GvdbReaderError::Context { ref error, .. } if matches!(**error, GvdbReaderError::DataError(_)));
+        let err = GvdbReaderError::InvalidData.with_item(&[], None);
+        assert_matches!(err, GvdbReaderError::InvalidData);
+        assert!(err.context().is_none());
+
         let err = GvdbReaderError::from(zvariant::Error::Message("test".to_string()));
         assert!(format!("{}", err).contains("test"));
 
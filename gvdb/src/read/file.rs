@@ -2,31 +2,69 @@ use crate::read::error::{GvdbReaderError, GvdbReaderResult};
 use crate::read::hash_item::{GvdbHashItem, GvdbHashItemType};
 use crate::read::header::GvdbHeader;
 use crate::read::pointer::GvdbPointer;
-use crate::read::GvdbHashTable;
+#[cfg(feature = "std")]
+use crate::read::GvdbPathItem;
+use crate::read::{GvdbHashTable, GvdbReaderLimits};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::{Debug, Formatter};
+use core::mem::size_of;
 use safe_transmute::transmute_one_pedantic;
-use std::borrow::Cow;
+#[cfg(feature = "std")]
 use std::fs::File;
+#[cfg(feature = "std")]
 use std::io::Read;
-use std::mem::size_of;
+#[cfg(feature = "std")]
 use std::path::Path;
 
-#[derive(Debug)]
-pub(crate) enum GvdbData {
-    Cow(Cow<'static, [u8]>),
+/// Byte storage a [`GvdbFile`] can be read from
+///
+/// This is implemented for every type that can be borrowed as a byte slice and is safe to share
+/// between threads, like `Vec<u8>`, `&[u8]`, `Arc<[u8]>`, `bytes::Bytes`, `glib::Bytes` or
+/// `memmap2::Mmap`. Use [`GvdbFile::from_storage`] to create a file without copying the data.
+pub trait GvdbStorage: AsRef<[u8]> + Send + Sync {}
+
+impl<T: AsRef<[u8]> + Send + Sync + ?Sized> GvdbStorage for T {}
+
+pub(crate) enum GvdbData<'a> {
+    Cow(Cow<'a, [u8]>),
     #[cfg(feature = "mmap")]
     Mmap(memmap2::Mmap),
+    #[cfg(feature = "glib")]
+    GlibBytes(glib::Bytes),
+    Storage(Box<dyn GvdbStorage + 'a>),
 }
 
-impl AsRef<[u8]> for GvdbData {
+impl<'a> AsRef<[u8]> for GvdbData<'a> {
     fn as_ref(&self) -> &[u8] {
         match self {
             GvdbData::Cow(cow) => cow.as_ref(),
             #[cfg(feature = "mmap")]
             GvdbData::Mmap(mmap) => mmap.as_ref(),
+            #[cfg(feature = "glib")]
+            GvdbData::GlibBytes(bytes) => bytes.as_ref(),
+            GvdbData::Storage(storage) => (**storage).as_ref(),
         }
     }
 }
 
+impl<'a> Debug for GvdbData<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            GvdbData::Cow(_) => "Cow",
+            #[cfg(feature = "mmap")]
+            GvdbData::Mmap(_) => "Mmap",
+            #[cfg(feature = "glib")]
+            GvdbData::GlibBytes(_) => "GlibBytes",
+            GvdbData::Storage(_) => "Storage",
+        };
+
+        write!(f, "{} {{ len: {} }}", name, self.as_ref().len())
+    }
+}
+
 /// The root of a GVDB file
 ///
 /// # Examples
@@ -86,22 +124,34 @@ impl AsRef<[u8]> for GvdbData {
 /// }
 /// ```
 #[derive(Debug)]
-pub struct GvdbFile {
-    pub(crate) data: GvdbData,
+pub struct GvdbFile<'a> {
+    pub(crate) data: GvdbData<'a>,
     pub(crate) byteswapped: bool,
+    pub(crate) limits: GvdbReaderLimits,
 }
 
-impl GvdbFile {
+impl<'a> GvdbFile<'a> {
     /// Get the GVDB file header. Will err with GvdbError::DataOffset if the header doesn't fit
     pub(crate) fn get_header(&self) -> GvdbReaderResult<GvdbHeader> {
         let header_data = self
             .data
             .as_ref()
             .get(0..size_of::<GvdbHeader>())
-            .ok_or(GvdbReaderError::DataOffset)?;
+            .ok_or_else(|| GvdbReaderError::DataOffset.with_range(0, size_of::<GvdbHeader>(), 1))?;
         Ok(transmute_one_pedantic(header_data)?)
     }
 
+    /// The limits that are enforced when reading this file
+    pub fn limits(&self) -> &GvdbReaderLimits {
+        &self.limits
+    }
+
+    /// Change the limits that are enforced when reading this file. This should be done before
+    /// accessing any hash tables.
+    pub fn set_limits(&mut self, limits: GvdbReaderLimits) {
+        self.limits = limits;
+    }
+
     /// Returns the root hash table of the file
     pub fn hash_table(&self) -> GvdbReaderResult<GvdbHashTable> {
         let header = self.get_header()?;
@@ -109,6 +159,24 @@ impl GvdbFile {
         GvdbHashTable::for_bytes(self.dereference(root_ptr, 4)?, self)
     }
 
+    /// Look up an item in the root hash table by following `path` through nested hash tables and
+    /// the containers of the path separator `/`. See [`GvdbHashTable::lookup_path`].
+    ///
+    /// ```
+    /// # use gvdb::read::{GvdbFile, GvdbPathItem};
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let item = file.lookup_path(["table", "int"]).unwrap();
+    /// assert!(matches!(item, GvdbPathItem::Value(value) if value == 42u32.into()));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn lookup_path<S: AsRef<str>>(
+        &self,
+        path: impl IntoIterator<Item = S>,
+    ) -> GvdbReaderResult<GvdbPathItem> {
+        self.hash_table()?.lookup_path(path)
+    }
+
     /// Dereference a pointer
     pub(crate) fn dereference(
         &self,
@@ -129,17 +197,18 @@ impl GvdbFile {
                 .get(start..end)
                 .ok_or(GvdbReaderError::DataOffset)
         }
+        .map_err(|err| err.with_range(start, end, alignment))
     }
 
-    fn read_header(&mut self) -> GvdbReaderResult<()> {
-        let header = self.get_header()?;
+    /// Validate the header and return whether the file is byteswapped
+    pub(crate) fn check_header(header: &GvdbHeader) -> GvdbReaderResult<bool> {
         if !header.header_valid() {
             return Err(GvdbReaderError::DataError(
                 "Invalid GVDB header. Is this a GVDB file?".to_string(),
             ));
         }
 
-        self.byteswapped = header.is_byteswap()?;
+        let byteswapped = header.is_byteswap()?;
 
         if header.version() != 0 {
             return Err(GvdbReaderError::DataError(format!(
@@ -148,14 +217,19 @@ impl GvdbFile {
             )));
         }
 
+        Ok(byteswapped)
+    }
+
+    fn read_header(&mut self) -> GvdbReaderResult<()> {
+        self.byteswapped = Self::check_header(&self.get_header()?)?;
         Ok(())
     }
 
-    /// Interpret a slice of bytes as a GVDB file
-    pub fn from_bytes(bytes: Cow<'static, [u8]>) -> GvdbReaderResult<GvdbFile> {
+    pub(crate) fn from_data(data: GvdbData<'a>) -> GvdbReaderResult<Self> {
         let mut this = Self {
-            data: GvdbData::Cow(bytes),
+            data,
             byteswapped: false,
+            limits: GvdbReaderLimits::default(),
         };
 
         this.read_header()?;
@@ -163,12 +237,85 @@ impl GvdbFile {
         Ok(this)
     }
 
+    /// Interpret a slice of bytes as a GVDB file
+    ///
+    /// The bytes can be borrowed for any lifetime, the resulting file can't outlive them.
+    ///
+    /// ```
+    /// # use std::borrow::Cow;
+    /// # use gvdb::read::GvdbFile;
+    /// let data = std::fs::read("test-data/test2.gvdb").unwrap();
+    /// let file = GvdbFile::from_bytes(Cow::Borrowed(&data)).unwrap();
+    /// ```
+    ///
+    /// Without the `std` feature, this is the way to read a file, for example one that is
+    /// embedded in the binary as a `&'static [u8]`.
+    pub fn from_bytes(bytes: Cow<'a, [u8]>) -> GvdbReaderResult<Self> {
+        Self::from_data(GvdbData::Cow(bytes))
+    }
+
+    /// Interpret the data of `storage` as a GVDB file without copying it
+    ///
+    /// This accepts any [`GvdbStorage`], which includes shared buffers like `Arc<[u8]>`,
+    /// `bytes::Bytes` or `glib::Bytes`.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use gvdb::read::GvdbFile;
+    /// let data: Arc<[u8]> = std::fs::read("test-data/test2.gvdb").unwrap().into();
+    /// let file = GvdbFile::from_storage(data.clone()).unwrap();
+    /// ```
+    pub fn from_storage(storage: impl GvdbStorage + 'a) -> GvdbReaderResult<Self> {
+        Self::from_data(GvdbData::Storage(Box::new(storage)))
+    }
+
+    /// Interpret the data of a [`struct@glib::Bytes`] as a GVDB file without copying it
+    ///
+    /// Unlike files from other storage, the variants returned by
+    /// [`get_gvariant`](GvdbHashTable::get_gvariant) share the data of `bytes` instead of copying
+    /// it, unless the file has to be byteswapped.
+    ///
+    /// ```
+    /// # use gvdb::read::GvdbFile;
+    /// let bytes = glib::Bytes::from_owned(std::fs::read("test-data/test3.gresource").unwrap());
+    /// let file = GvdbFile::from_glib_bytes(bytes).unwrap();
+    /// ```
+    #[cfg(feature = "glib")]
+    pub fn from_glib_bytes(bytes: glib::Bytes) -> GvdbReaderResult<Self> {
+        Self::from_data(GvdbData::GlibBytes(bytes))
+    }
+
     /// Open a file and interpret the data as GVDB
     /// ```
     /// let path = std::path::PathBuf::from("test-data/test3.gresource");
     /// let file = gvdb::read::GvdbFile::from_file(&path).unwrap();
     /// ```
+    #[cfg(feature = "std")]
     pub fn from_file(filename: &Path) -> GvdbReaderResult<Self> {
+        Self::from_bytes(Cow::Owned(Self::read_file(filename)?))
+    }
+
+    /// Like [`from_file()`](Self::from_file), but the file is read on a separate thread, so
+    /// async executors like tokio are not blocked while reading large files
+    ///
+    /// Each call spawns a new OS thread, which is cheap compared to reading a large file, but
+    /// not worth it for small ones.
+    ///
+    /// ```
+    /// # futures_executor::block_on(async {
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = gvdb::read::GvdbFile::from_file_async(&path).await.unwrap();
+    /// # });
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn from_file_async(filename: &Path) -> GvdbReaderResult<Self> {
+        let filename = filename.to_path_buf();
+        let data = crate::util::spawn_blocking(move || Self::read_file(&filename)).await?;
+        Self::from_bytes(Cow::Owned(data))
+    }
+
+    #[cfg(feature = "std")]
+    fn read_file(filename: &Path) -> GvdbReaderResult<Vec<u8>> {
         let mut file =
             File::open(filename).map_err(GvdbReaderError::from_io_with_filename(filename))?;
         let mut data = Vec::with_capacity(
@@ -178,7 +325,7 @@ impl GvdbFile {
         );
         file.read_to_end(&mut data)
             .map_err(GvdbReaderError::from_io_with_filename(filename))?;
-        Self::from_bytes(Cow::Owned(data))
+        Ok(data)
     }
 
     /// Open a file and `mmap` it into memory.
@@ -187,31 +334,103 @@ impl GvdbFile {
     ///
     /// This is marked unsafe as the file could be modified on-disk while the mmap is active.
     /// This will cause undefined behavior. You must make sure to employ your own locking and to
-    /// reload the file yourself when any modification occurs.
+    /// reload the file yourself when any modification occurs. With the `reload` feature,
+    /// [`ReloadingGvdbFile`](crate::read::ReloadingGvdbFile) takes care of the reloading.
+    ///
+    /// Use [`GvdbMmapOptions`](crate::read::GvdbMmapOptions) to configure the mapping.
     #[cfg(feature = "mmap")]
     pub unsafe fn from_file_mmap(filename: &Path) -> GvdbReaderResult<Self> {
-        let file =
-            File::open(filename).map_err(GvdbReaderError::from_io_with_filename(filename))?;
-        let mmap =
-            memmap2::Mmap::map(&file).map_err(GvdbReaderError::from_io_with_filename(filename))?;
+        crate::read::GvdbMmapOptions::new().map(filename)
+    }
 
-        let mut this = Self {
-            data: GvdbData::Mmap(mmap),
-            byteswapped: false,
-        };
+    /// Map a sealed memfd into memory and interpret the data as GVDB
+    ///
+    /// Unlike [`from_file_mmap`](Self::from_file_mmap) this is safe, as the seals are checked
+    /// before mapping: A memfd with `F_SEAL_WRITE` and `F_SEAL_SHRINK` can't be modified or
+    /// truncated anymore. Fails with an [`Io`](GvdbReaderError::Io) error of kind
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput) if the seals are missing.
+    ///
+    /// Use [`GvdbFileWriter::write_to_sealed_memfd`](crate::write::GvdbFileWriter::write_to_sealed_memfd)
+    /// to create such a memfd.
+    ///
+    /// ```
+    /// # use gvdb::read::GvdbFile;
+    /// # use gvdb::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.insert_string("string", "test string").unwrap();
+    /// let fd = GvdbFileWriter::new().write_to_sealed_memfd(table_builder).unwrap();
+    ///
+    /// let file = GvdbFile::from_sealed_memfd(&fd).unwrap();
+    /// let value: String = file.hash_table().unwrap().get("string").unwrap();
+    /// assert_eq!(value, "test string");
+    /// ```
+    #[cfg(all(feature = "memfd", target_os = "linux"))]
+    pub fn from_sealed_memfd(fd: impl std::os::unix::io::AsFd) -> GvdbReaderResult<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let fd = fd.as_fd();
+        let seals = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GET_SEALS) };
+        if seals < 0 {
+            return Err(GvdbReaderError::Io(std::io::Error::last_os_error(), None));
+        }
 
-        this.read_header()?;
+        let required = libc::F_SEAL_WRITE | libc::F_SEAL_SHRINK;
+        if seals & required != required {
+            return Err(GvdbReaderError::Io(
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "memfd is not sealed with F_SEAL_WRITE and F_SEAL_SHRINK",
+                ),
+                None,
+            ));
+        }
 
-        Ok(this)
+        // Safety: The seals guarantee that the contents can't change and the file can't shrink
+        // while it is mapped. The seals can't be removed once set.
+        let mmap = unsafe { memmap2::Mmap::map(fd.as_raw_fd()) }
+            .map_err(|err| GvdbReaderError::Io(err, None))?;
+
+        Self::from_data(GvdbData::Mmap(mmap))
+    }
+
+    /// Advise the kernel that the bytes from `start` to `end` will be needed soon.
+    /// This only has an effect on memory-mapped files.
+    #[cfg_attr(not(all(feature = "mmap", unix)), allow(unused_variables))]
+    pub(crate) fn prefetch_range(&self, start: usize, end: usize) -> GvdbReaderResult<()> {
+        #[cfg(all(feature = "mmap", unix))]
+        if let GvdbData::Mmap(mmap) = &self.data {
+            let end = end.min(mmap.len());
+            if start < end {
+                mmap.advise_range(memmap2::Advice::WillNeed, start, end - start)
+                    .map_err(|err| GvdbReaderError::Io(err, None))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The offset of `data` in the file, if it is a slice of the file data
+    pub(crate) fn offset_of(&self, data: &[u8]) -> Option<usize> {
+        let file_start = self.data.as_ref().as_ptr() as usize;
+        let start = data.as_ptr() as usize;
+        let offset = start.checked_sub(file_start)?;
+        if offset + data.len() <= self.data.as_ref().len() {
+            Some(offset)
+        } else {
+            None
+        }
     }
 
     /// gvdb_table_item_get_key
     pub(crate) fn get_key(&self, item: &GvdbHashItem) -> GvdbReaderResult<String> {
-        let data = self.dereference(&item.key_ptr(), 1)?;
+        let key_ptr = item.key_ptr();
+        self.limits.check_key_length(key_ptr.size())?;
+        let data = self.dereference(&key_ptr, 1)?;
         Ok(String::from_utf8(data.to_vec())?)
     }
 
-    fn get_bytes_for_item(&self, item: &GvdbHashItem) -> GvdbReaderResult<&[u8]> {
+    #[cfg(feature = "std")]
+    pub(crate) fn get_bytes_for_item(&self, item: &GvdbHashItem) -> GvdbReaderResult<&[u8]> {
         let typ = item.typ()?;
         if typ == GvdbHashItemType::Value {
             Ok(self.dereference(item.value_ptr(), 8)?)
@@ -230,7 +449,13 @@ impl GvdbFile {
         item: &GvdbHashItem,
     ) -> GvdbReaderResult<glib::Variant> {
         let data = self.get_bytes_for_item(item)?;
-        let variant = glib::Variant::from_data_with_type(data, glib::VariantTy::VARIANT);
+        let bytes = match (&self.data, self.offset_of(data)) {
+            (GvdbData::GlibBytes(bytes), Some(offset)) => {
+                crate::util::slice_glib_bytes(bytes, offset..offset + data.len())
+            }
+            _ => glib::Bytes::from(data),
+        };
+        let variant = glib::Variant::from_bytes_with_type(&bytes, glib::VariantTy::VARIANT);
 
         if self.byteswapped {
             Ok(variant.byteswap())
@@ -239,17 +464,15 @@ impl GvdbFile {
         }
     }
 
-    pub(crate) fn get_value_for_item(
-        &self,
-        item: &GvdbHashItem,
-    ) -> GvdbReaderResult<zvariant::Value> {
-        let data = self.get_bytes_for_item(item)?;
+    /// Deserialize the data of a `v` item, taking the byte order of the file into account
+    #[cfg(feature = "std")]
+    pub(crate) fn parse_value(data: &[u8], byteswapped: bool) -> GvdbReaderResult<zvariant::Value> {
         #[cfg(target_endian = "little")]
         let le = true;
         #[cfg(target_endian = "big")]
         let le = false;
 
-        if le && !self.byteswapped || !le && self.byteswapped {
+        if le && !byteswapped || !le && byteswapped {
             let context = zvariant::EncodingContext::<byteorder::LE>::new_gvariant(0);
             Ok(zvariant::from_slice(data, context)?)
         } else {
@@ -258,6 +481,15 @@ impl GvdbFile {
         }
     }
 
+    #[cfg(feature = "std")]
+    pub(crate) fn get_value_for_item(
+        &self,
+        item: &GvdbHashItem,
+    ) -> GvdbReaderResult<zvariant::Value> {
+        let data = self.get_bytes_for_item(item)?;
+        Self::parse_value(data, self.byteswapped)
+    }
+
     pub(crate) fn get_hash_table_for_item(
         &self,
         item: &GvdbHashItem,
@@ -275,12 +507,13 @@ impl GvdbFile {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(feature = "std", test))]
 mod test {
     use crate::read::file::GvdbFile;
     use std::borrow::Cow;
     use std::mem::size_of;
     use std::path::PathBuf;
+    use std::sync::Arc;
 
     use crate::read::{GvdbHeader, GvdbPointer, GvdbReaderError};
     use crate::test::*;
@@ -296,6 +529,17 @@ mod test {
         assert_is_file_1(&file);
     }
 
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_file_async() {
+        let file = futures_executor::block_on(GvdbFile::from_file_async(&TEST_FILE_1)).unwrap();
+        assert_is_file_1(&file);
+
+        let path = TEST_FILE_DIR.join("missing.gvdb");
+        let err = futures_executor::block_on(GvdbFile::from_file_async(&path)).unwrap_err();
+        assert_matches!(err, GvdbReaderError::Io(_, Some(filename)) if filename == path);
+    }
+
     #[cfg(feature = "mmap")]
     #[test]
     fn test_file_1_mmap() {
@@ -309,6 +553,65 @@ mod test {
         assert_is_file_2(&file);
     }
 
+    #[cfg(all(feature = "memfd", target_os = "linux"))]
+    #[test]
+    fn test_file_2_sealed_memfd() {
+        use std::io::Write;
+        use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder
+            .insert_string("string", "test string")
+            .unwrap();
+        let mut table_builder_2 = GvdbHashTableBuilder::new();
+        table_builder_2.insert("int", 42u32).unwrap();
+        table_builder
+            .insert_table("table", table_builder_2)
+            .unwrap();
+
+        let fd = GvdbFileWriter::new()
+            .write_to_sealed_memfd(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_sealed_memfd(&fd).unwrap();
+        drop(fd);
+        assert_is_file_2(&file);
+
+        // Regular files can't be sealed
+        let file = std::fs::File::open(&*TEST_FILE_2).unwrap();
+        assert_matches!(
+            GvdbFile::from_sealed_memfd(&file),
+            Err(GvdbReaderError::Io(_, None))
+        );
+
+        // A memfd without seals
+        let fd = unsafe {
+            OwnedFd::from_raw_fd(libc::memfd_create(
+                b"gvdb-test\0".as_ptr().cast(),
+                libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+            ))
+        };
+        let mut memfd = std::fs::File::from(fd);
+        memfd
+            .write_all(&std::fs::read(&*TEST_FILE_2).unwrap())
+            .unwrap();
+        match GvdbFile::from_sealed_memfd(&memfd) {
+            Err(GvdbReaderError::Io(err, None)) => {
+                assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput)
+            }
+            other => panic!("Unexpected result: {:?}", other),
+        }
+
+        // Sealing only against growing is not enough
+        assert_eq!(
+            unsafe { libc::fcntl(memfd.as_raw_fd(), libc::F_ADD_SEALS, libc::F_SEAL_GROW) },
+            0
+        );
+        assert_matches!(
+            GvdbFile::from_sealed_memfd(&memfd),
+            Err(GvdbReaderError::Io(_, None))
+        );
+    }
+
     #[test]
     fn test_file_3() {
         let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
@@ -356,7 +659,7 @@ mod test {
         }
     }
 
-    fn create_minimal_file() -> GvdbFile {
+    fn create_minimal_file() -> GvdbFile<'static> {
         let header = GvdbHeader::new(false, 0, GvdbPointer::new(0, 0));
         let data = transmute_one_to_bytes(&header).to_vec();
         assert_bytes_eq(
@@ -370,6 +673,37 @@ mod test {
         GvdbFile::from_bytes(Cow::Owned(data)).unwrap()
     }
 
+    #[test]
+    fn test_file_2_borrowed() {
+        let data = std::fs::read(&*TEST_FILE_2).unwrap();
+        let file = GvdbFile::from_bytes(Cow::Borrowed(&data)).unwrap();
+        assert_is_file_2(&file);
+    }
+
+    #[test]
+    fn test_file_2_storage() {
+        let data = std::fs::read(&*TEST_FILE_2).unwrap();
+
+        {
+            let file = GvdbFile::from_storage(&data[..]).unwrap();
+            assert_is_file_2(&file);
+            assert!(format!("{:?}", file).contains("Storage"));
+        }
+
+        let shared: Arc<[u8]> = data.clone().into();
+        let file = GvdbFile::from_storage(shared.clone()).unwrap();
+        assert_is_file_2(&file);
+        drop(file);
+        assert_eq!(Arc::strong_count(&shared), 1);
+
+        let file = GvdbFile::from_storage(data).unwrap();
+        assert_is_file_2(&file);
+
+        let err = GvdbFile::from_storage(vec![0u8; 10]).unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataOffset);
+        assert_eq!(err.context().unwrap().range(), Some(0..24));
+    }
+
     #[test]
     fn test_minimal_file() {
         let _ = create_minimal_file();
@@ -421,40 +755,44 @@ mod test {
     fn test_dereference_offset1() {
         // Pointer start > EOF
         let file = create_minimal_file();
-        let res = file.dereference(&GvdbPointer::new(40, 42), 2);
+        let err = file.dereference(&GvdbPointer::new(40, 42), 2).unwrap_err();
 
-        assert_matches!(res, Err(GvdbReaderError::DataOffset));
-        println!("{}", res.unwrap_err());
+        assert_matches!(err.inner(), GvdbReaderError::DataOffset);
+        assert_eq!(err.context().unwrap().range(), Some(40..42));
+        println!("{}", err);
     }
 
     #[test]
     fn test_dereference_offset2() {
         // Pointer start > end
         let file = create_minimal_file();
-        let res = file.dereference(&GvdbPointer::new(10, 0), 2);
+        let err = file.dereference(&GvdbPointer::new(10, 0), 2).unwrap_err();
 
-        assert_matches!(res, Err(GvdbReaderError::DataOffset));
-        println!("{}", res.unwrap_err());
+        assert_matches!(err.inner(), GvdbReaderError::DataOffset);
+        assert!(err.context().is_some());
+        println!("{}", err);
     }
 
     #[test]
     fn test_dereference_offset3() {
         // Pointer end > EOF
         let file = create_minimal_file();
-        let res = file.dereference(&GvdbPointer::new(10, 0), 2);
+        let err = file.dereference(&GvdbPointer::new(10, 0), 2).unwrap_err();
 
-        assert_matches!(res, Err(GvdbReaderError::DataOffset));
-        println!("{}", res.unwrap_err());
+        assert_matches!(err.inner(), GvdbReaderError::DataOffset);
+        assert!(err.context().is_some());
+        println!("{}", err);
     }
 
     #[test]
     fn test_dereference_alignment() {
         // Pointer end > EOF
         let file = create_minimal_file();
-        let res = file.dereference(&GvdbPointer::new(1, 2), 2);
+        let err = file.dereference(&GvdbPointer::new(1, 2), 2).unwrap_err();
 
-        assert_matches!(res, Err(GvdbReaderError::DataAlignment));
-        println!("{}", res.unwrap_err());
+        assert_matches!(err.inner(), GvdbReaderError::DataAlignment);
+        assert_eq!(err.context().unwrap().range(), Some(1..2));
+        println!("{}", err);
     }
 
     #[test]
@@ -464,15 +802,46 @@ mod test {
         let table = file.hash_table().unwrap();
 
         // A table isn't a value
-        let table_res = table.get_value("table");
-        assert_matches!(table_res, Err(GvdbReaderError::DataError(_)));
+        let err = table.get_value("table").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataError(_));
+        let index = table.find_hash_item("table").unwrap().0;
+        assert_eq!(err.context().unwrap().item_index(), Some(index));
     }
 
     #[test]
     fn test_nested_dict_fail() {
         let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
         let table = file.hash_table().unwrap();
-        let res = table.get_hash_table("string");
-        assert_matches!(res, Err(GvdbReaderError::DataError(_)));
+        let err = table.get_hash_table("string").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataError(_));
+    }
+}
+
+#[cfg(all(not(feature = "std"), test))]
+mod test_no_std {
+    use crate::read::{GvdbFile, GvdbHashItemType, GvdbReaderError};
+    use alloc::borrow::Cow;
+
+    // Aligned the same way as the data of the include_gresource macros
+    #[repr(align(16))]
+    struct Aligned<T: ?Sized>(T);
+    static TEST_FILE_2: &Aligned<[u8]> = &Aligned(*include_bytes!("../../test-data/test2.gvdb"));
+
+    #[test]
+    fn read_static() {
+        let file = GvdbFile::from_bytes(Cow::Borrowed(&TEST_FILE_2.0)).unwrap();
+        let root = file.hash_table().unwrap();
+        assert_eq!(root.get_names().unwrap(), ["string", "table"]);
+
+        let table = root.get_hash_table("table").unwrap();
+        let info = table.get_item_info("int").unwrap();
+        assert_eq!(info.typ(), GvdbHashItemType::Value);
+        assert_eq!(info.signature(), Some("u"));
+        // test2.gvdb is a big endian file
+        assert_eq!(info.value_bytes(), Some(&[0, 0, 0, 42][..]));
+
+        let err = table.get_item_info("missing").unwrap_err();
+        assert!(matches!(err.inner(), GvdbReaderError::KeyError(_)));
+        assert_eq!(err.context().unwrap().table_path(), ["table"]);
     }
 }
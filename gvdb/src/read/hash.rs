@@ -1,14 +1,18 @@
 use crate::read::error::{GvdbReaderError, GvdbReaderResult};
 use crate::read::file::GvdbFile;
-use crate::read::hash_item::GvdbHashItem;
+use crate::read::hash_item::{GvdbHashItem, GvdbHashItemType, GvdbItemInfo};
+use crate::read::key::GvdbKey;
 use crate::util::djb_hash;
-use safe_transmute::{
-    transmute_many_pedantic, transmute_one, transmute_one_pedantic, TriviallyTransmutable,
-};
-use std::borrow::Cow;
-use std::cmp::{max, min};
-use std::fmt::{Debug, Formatter};
-use std::mem::size_of;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::fmt::{Debug, Formatter};
+use core::mem::size_of;
+use core::ops::Range;
+use safe_transmute::{transmute_one, transmute_one_pedantic, TriviallyTransmutable};
 
 /// The header of a GVDB hash table
 #[repr(C)]
@@ -23,9 +27,12 @@ unsafe impl TriviallyTransmutable for GvdbHashHeader {}
 impl GvdbHashHeader {
     /// Create a new GvdbHashHeader using the provided `bloom_shift`, `n_bloom_words` and
     /// `n_buckets`
+    ///
+    /// `bloom_shift` has to be smaller than 32 and `n_bloom_words` smaller than 2^27, larger
+    /// values are truncated.
     pub fn new(bloom_shift: u32, n_bloom_words: u32, n_buckets: u32) -> Self {
-        assert!(n_bloom_words < (1 << 27));
-        let n_bloom_words = bloom_shift << 27 | n_bloom_words;
+        debug_assert!(bloom_shift < 32 && n_bloom_words < (1 << 27));
+        let n_bloom_words = (bloom_shift & 31) << 27 | (n_bloom_words & ((1 << 27) - 1));
 
         Self {
             n_bloom_words: n_bloom_words.to_le(),
@@ -38,9 +45,14 @@ impl GvdbHashHeader {
         u32::from_le(self.n_bloom_words) & ((1 << 27) - 1)
     }
 
+    /// The shift used to calculate the second bloom filter bit for a hash value
+    pub fn bloom_shift(&self) -> u32 {
+        u32::from_le(self.n_bloom_words) >> 27
+    }
+
     /// Size of the bloom words section in the header
     pub fn bloom_words_len(&self) -> usize {
-        self.n_bloom_words() as usize * size_of::<u32>()
+        (self.n_bloom_words() as usize).saturating_mul(size_of::<u32>())
     }
 
     /// Number of hash buckets in the hash table header
@@ -50,12 +62,12 @@ impl GvdbHashHeader {
 
     /// Length of the hash buckets section in the header
     pub fn buckets_len(&self) -> usize {
-        self.n_buckets() as usize * size_of::<u32>()
+        (self.n_buckets() as usize).saturating_mul(size_of::<u32>())
     }
 }
 
 impl Debug for GvdbHashHeader {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "GvdbHashHeader {{ n_bloom_words: {}, n_buckets: {} }}",
@@ -65,25 +77,21 @@ impl Debug for GvdbHashHeader {
     }
 }
 
-/// A hash table inside a GVDB file
-///
+/// The structure of a hash table: header, bloom filter, buckets and items
 ///
-#[repr(C)]
+/// Keys and values are stored outside of the hash table structure, so every lookup that needs to
+/// compare keys takes a function to read the key of an item.
 #[derive(Clone, Debug)]
-pub struct GvdbHashTable<'a> {
-    pub(crate) root: &'a GvdbFile,
+pub(crate) struct GvdbHashTableData<'a> {
     data: Cow<'a, [u8]>,
     header: GvdbHashHeader,
 }
 
-impl<'a> GvdbHashTable<'a> {
-    /// Interpret a chunk of bytes as a HashTable. The table_ptr should point to the hash table.
-    /// Data has to be the complete GVDB file, as hash table items are stored somewhere else.
-    pub fn for_bytes(data: &'a [u8], root: &'a GvdbFile) -> GvdbReaderResult<Self> {
-        let header = Self::hash_header(data)?;
-        let data = Cow::Borrowed(data);
-
-        let this = Self { root, data, header };
+impl<'a> GvdbHashTableData<'a> {
+    /// Interpret a chunk of bytes as the structure of a hash table
+    pub fn for_bytes(data: Cow<'a, [u8]>) -> GvdbReaderResult<Self> {
+        let header = Self::hash_header(&data)?;
+        let this = Self { data, header };
 
         let header_len = size_of::<GvdbHashHeader>();
         let bloom_words_len = this.bloom_words_end() - this.bloom_words_offset();
@@ -92,7 +100,10 @@ impl<'a> GvdbHashTable<'a> {
         // we use max() here to prevent possible underflow
         let hash_items_len =
             max(this.hash_items_end(), this.hash_items_offset()) - this.hash_items_offset();
-        let required_len = header_len + bloom_words_len + hash_buckets_len + hash_items_len;
+        let required_len = header_len
+            .saturating_add(bloom_words_len)
+            .saturating_add(hash_buckets_len)
+            .saturating_add(hash_items_len);
 
         if required_len > this.data.len() {
             Err(GvdbReaderError::DataError(format!(
@@ -113,7 +124,7 @@ impl<'a> GvdbHashTable<'a> {
     }
 
     /// Read the hash table header
-    fn hash_header(data: &'a [u8]) -> GvdbReaderResult<GvdbHashHeader> {
+    pub fn hash_header(data: &[u8]) -> GvdbReaderResult<GvdbHashHeader> {
         let bytes: &[u8] = data
             .get(0..size_of::<GvdbHashHeader>())
             .ok_or(GvdbReaderError::DataOffset)?;
@@ -121,8 +132,11 @@ impl<'a> GvdbHashTable<'a> {
         Ok(transmute_one(bytes)?)
     }
 
-    /// Returns the header for this hash table
-    pub fn get_header(&self) -> GvdbHashHeader {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn header(&self) -> GvdbHashHeader {
         self.header
     }
 
@@ -134,23 +148,27 @@ impl<'a> GvdbHashTable<'a> {
         Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
     }
 
-    fn bloom_words_offset(&self) -> usize {
+    pub fn bloom_words_offset(&self) -> usize {
         size_of::<GvdbHashHeader>()
     }
 
     fn bloom_words_end(&self) -> usize {
-        self.bloom_words_offset() + self.header.bloom_words_len()
+        self.bloom_words_offset()
+            .saturating_add(self.header.bloom_words_len())
     }
 
     /// Returns the bloom words for this hash table
-    #[allow(dead_code)]
-    fn bloom_words(&self) -> Option<&[u32]> {
+    #[cfg(feature = "std")]
+    pub fn bloom_words(&self) -> Option<&[u32]> {
         // This indexing operation is safe as data is guaranteed to be larger than
         // bloom_words_offset and this will just return an empty slice if end == offset
-        transmute_many_pedantic(&self.data[self.bloom_words_offset()..self.bloom_words_end()]).ok()
+        safe_transmute::transmute_many_pedantic(
+            &self.data[self.bloom_words_offset()..self.bloom_words_end()],
+        )
+        .ok()
     }
 
-    fn get_bloom_word(&self, index: usize) -> GvdbReaderResult<u32> {
+    pub fn get_bloom_word(&self, index: usize) -> GvdbReaderResult<u32> {
         if index >= self.header.n_bloom_words() as usize {
             return Err(GvdbReaderError::DataOffset);
         }
@@ -159,13 +177,12 @@ impl<'a> GvdbHashTable<'a> {
         self.get_u32(start)
     }
 
-    // TODO: Calculate proper bloom shift
-    fn bloom_shift(&self) -> usize {
-        0
+    pub fn bloom_shift(&self) -> usize {
+        self.header.bloom_shift() as usize
     }
 
     /// Check whether the hash value corresponds to the bloom filter
-    fn bloom_filter(&self, hash_value: u32) -> bool {
+    pub fn bloom_filter(&self, hash_value: u32) -> bool {
         if self.header.n_bloom_words() == 0 {
             return true;
         }
@@ -174,29 +191,53 @@ impl<'a> GvdbHashTable<'a> {
         let mut mask = 1 << (hash_value & 31);
         mask |= 1 << ((hash_value >> self.bloom_shift()) & 31);
 
-        // We know this index is < n_bloom_words
-        let bloom_word = self.get_bloom_word(word as usize).unwrap();
-        bloom_word & mask == mask
+        // The index is < n_bloom_words, and all bloom words were checked to be present in
+        // for_bytes(). Should this fail anyway, fall back to looking at the hash buckets.
+        match self.get_bloom_word(word as usize) {
+            Ok(bloom_word) => bloom_word & mask == mask,
+            Err(_) => true,
+        }
     }
 
-    fn hash_buckets_offset(&self) -> usize {
+    pub fn hash_buckets_offset(&self) -> usize {
         self.bloom_words_end()
     }
 
     fn hash_buckets_end(&self) -> usize {
-        self.hash_buckets_offset() + self.header.buckets_len()
+        self.hash_buckets_offset()
+            .saturating_add(self.header.buckets_len())
     }
 
-    fn get_hash(&self, index: usize) -> GvdbReaderResult<u32> {
+    pub fn get_hash(&self, index: usize) -> GvdbReaderResult<u32> {
         let start = self.hash_buckets_offset() + index * size_of::<u32>();
         self.get_u32(start)
     }
 
-    fn hash_items_offset(&self) -> usize {
+    pub fn hash_items_offset(&self) -> usize {
         self.hash_buckets_end()
     }
 
-    fn n_hash_items(&self) -> usize {
+    /// The number of items in each hash bucket
+    #[cfg(feature = "std")]
+    pub fn bucket_sizes(&self) -> GvdbReaderResult<Vec<usize>> {
+        let n_buckets = self.header.n_buckets() as usize;
+        let n_items = self.n_hash_items();
+
+        (0..n_buckets)
+            .map(|bucket| {
+                let start = min(self.get_hash(bucket)? as usize, n_items);
+                let end = if bucket + 1 == n_buckets {
+                    n_items
+                } else {
+                    min(self.get_hash(bucket + 1)? as usize, n_items)
+                };
+
+                Ok(end.saturating_sub(start))
+            })
+            .collect()
+    }
+
+    pub fn n_hash_items(&self) -> usize {
         let len = self.hash_items_end() - self.hash_items_offset();
         len / size_of::<GvdbHashItem>()
     }
@@ -206,7 +247,7 @@ impl<'a> GvdbHashTable<'a> {
     }
 
     /// Get the hash item at hash item index
-    fn get_hash_item_for_index(&self, index: usize) -> GvdbReaderResult<GvdbHashItem> {
+    pub fn get_hash_item_for_index(&self, index: usize) -> GvdbReaderResult<GvdbHashItem> {
         let size = size_of::<GvdbHashItem>();
         let start = self.hash_items_offset() + size * index;
         let end = start + size;
@@ -214,101 +255,150 @@ impl<'a> GvdbHashTable<'a> {
         let data = self
             .data
             .get(start..end)
-            .ok_or(GvdbReaderError::DataOffset)?;
+            .ok_or(GvdbReaderError::DataOffset)
+            .map_err(|err| err.with_item(&[], Some(index)))?;
         Ok(transmute_one_pedantic(data)?)
     }
 
-    /// Gets a list of keys contained in the hash table
-    pub fn get_names(&self) -> GvdbReaderResult<Vec<String>> {
+    /// Gets a list of the full keys of all items in the hash table, in the order the items are
+    /// stored in the file
+    ///
+    /// Every item stores the key relative to its parent item. The full keys are built in a single
+    /// pass over the items, resolving every parent only once.
+    pub fn get_names(
+        &self,
+        get_key: impl Fn(&GvdbHashItem) -> GvdbReaderResult<String>,
+    ) -> GvdbReaderResult<Vec<String>> {
         let count = self.n_hash_items();
-        let mut names = vec![None; count];
-
-        let mut inserted = 0;
-        while inserted < count {
-            let last_inserted = inserted;
-            for index in 0..count {
-                let item = self.get_hash_item_for_index(index)?;
-                let parent: usize = item.parent().try_into()?;
-
-                if names[index].is_none() {
-                    // Only process items not already processed
-                    if parent == 0xffffffff {
-                        // root item
-                        let name = self.get_key(&item)?;
-                        let _ = std::mem::replace(&mut names[index], Some(name));
-                        inserted += 1;
-                    } else if parent < count && names[parent].is_some() {
-                        // We already came across this item
-                        let name = self.get_key(&item)?;
-                        let parent_name = names.get(parent).unwrap().as_ref().unwrap();
-                        let full_name = parent_name.to_string() + &name;
-                        let _ = std::mem::replace(&mut names[index], Some(full_name));
-                        inserted += 1;
-                    } else if parent > count {
-                        return Err(GvdbReaderError::DataError(format!(
-                            "Parent with invalid offset encountered: {}",
-                            parent
-                        )));
-                    }
+        let mut names: Vec<Option<String>> = vec![None; count];
+        // Items whose parents are being resolved, to detect loops
+        let mut visited = vec![false; count];
+        let mut chain = Vec::new();
+
+        for index in 0..count {
+            // Follow the parents until we reach an item with a known name or a root item
+            let mut current = Some(index);
+            while let Some(current_index) = current {
+                if names[current_index].is_some() {
+                    break;
+                }
+
+                if visited[current_index] {
+                    return Err(parent_loop_error().with_item(&[], Some(current_index)));
                 }
+                visited[current_index] = true;
+
+                let item = self.get_hash_item_for_index(current_index)?;
+                let parent = item.parent();
+                chain.push((current_index, item));
+
+                current = if parent == u32::MAX {
+                    None
+                } else if (parent as usize) < count {
+                    Some(parent as usize)
+                } else {
+                    return Err(GvdbReaderError::DataError(format!(
+                        "Parent with invalid offset encountered: {}",
+                        parent
+                    ))
+                    .with_item(&[], Some(current_index)));
+                };
             }
 
-            if last_inserted == inserted {
-                // No insertion took place this round, there must be a parent loop
-                // We fail instead of infinitely looping
-                return Err(GvdbReaderError::DataError(
-                    "Error finding all parent items. The file appears to have a loop".to_string(),
-                ));
+            // Build the names of the items in the chain, starting at the top
+            let mut name = current
+                .and_then(|parent| names[parent].clone())
+                .unwrap_or_default();
+            while let Some((item_index, item)) = chain.pop() {
+                let key = get_key(&item).map_err(|err| err.with_item(&[], Some(item_index)))?;
+                name.push_str(&key);
+                names[item_index] = Some(name.clone());
             }
         }
 
-        let names = names.into_iter().map(|s| s.unwrap()).collect();
-        Ok(names)
+        Ok(names.into_iter().map(Option::unwrap_or_default).collect())
     }
 
-    fn check_name(&self, item: &GvdbHashItem, key: &str) -> bool {
-        let this_key = match self.get_key(item) {
-            Ok(this_key) => this_key,
-            Err(_) => return false,
-        };
+    /// Whether the key of `item`, including the keys of all of its parents, equals `key`
+    fn check_name(
+        &self,
+        item: &GvdbHashItem,
+        key: &str,
+        get_key: &impl Fn(&GvdbHashItem) -> GvdbReaderResult<String>,
+    ) -> bool {
+        let mut item = *item;
+        let mut key = key;
+
+        // Every item can only appear once in the chain of parents, unless there is a loop
+        for _ in 0..=self.n_hash_items() {
+            let this_key = match get_key(&item) {
+                Ok(this_key) => this_key,
+                Err(_) => return false,
+            };
 
-        if !key.ends_with(&this_key) {
-            return false;
-        }
+            if !key.ends_with(&this_key) {
+                return false;
+            }
 
-        let parent = item.parent();
-        if key.len() == this_key.len() && parent == 0xffffffff {
-            return true;
-        }
+            let parent = item.parent();
+            if key.len() == this_key.len() && parent == 0xffffffff {
+                return true;
+            }
+
+            if parent >= self.n_hash_items() as u32 || key.is_empty() {
+                return false;
+            }
 
-        if parent < self.n_hash_items() as u32 && !key.is_empty() {
-            let parent_item = match self.get_hash_item_for_index(parent as usize) {
+            item = match self.get_hash_item_for_index(parent as usize) {
                 Ok(p) => p,
                 Err(_) => return false,
             };
-
-            let parent_key_len = key.len() - this_key.len();
-            return self.check_name(&parent_item, &key[0..parent_key_len]);
+            key = &key[0..key.len() - this_key.len()];
         }
 
         false
     }
 
-    /// Gets the item at key `key`
-    pub fn get_hash_item(&self, key: &str) -> GvdbReaderResult<GvdbHashItem> {
-        if self.header.n_buckets() == 0 || self.n_hash_items() == 0 {
-            return Err(GvdbReaderError::KeyError(key.to_string()));
+    /// Gets the index of the item at key `key` and the item itself
+    pub fn find_hash_item(
+        &self,
+        key: &str,
+        get_key: impl Fn(&GvdbHashItem) -> GvdbReaderResult<String>,
+    ) -> GvdbReaderResult<(usize, GvdbHashItem)> {
+        self.find_hash_item_with_hash(key, djb_hash(key), &get_key)
+    }
+
+    /// Gets the index of the item at key `key`, whose hash value is `hash_value`, and the item
+    /// itself
+    pub fn find_hash_item_with_hash(
+        &self,
+        key: &str,
+        hash_value: u32,
+        get_key: &impl Fn(&GvdbHashItem) -> GvdbReaderResult<String>,
+    ) -> GvdbReaderResult<(usize, GvdbHashItem)> {
+        for itemno in self.bucket_items(hash_value)? {
+            let item = self.get_hash_item_for_index(itemno)?;
+            if hash_value == item.hash_value() && self.check_name(&item, key, get_key) {
+                return Ok((itemno, item));
+            }
         }
 
-        let hash_value = djb_hash(key);
-        if !self.bloom_filter(hash_value) {
-            return Err(GvdbReaderError::KeyError(key.to_string()));
+        Err(GvdbReaderError::KeyError(key.to_string()))
+    }
+
+    /// The indices of the items in the hash bucket of `hash_value`. Empty if the bloom filter
+    /// rules out that an item with this hash value exists.
+    pub fn bucket_items(&self, hash_value: u32) -> GvdbReaderResult<Range<usize>> {
+        if self.header.n_buckets() == 0
+            || self.n_hash_items() == 0
+            || !self.bloom_filter(hash_value)
+        {
+            return Ok(0..0);
         }
 
         let bucket = hash_value % self.header.n_buckets();
-        let mut itemno = self.get_hash(bucket as usize)? as usize;
-
-        let lastno = if bucket == self.header.n_buckets() - 1 {
+        let start = self.get_hash(bucket as usize)? as usize;
+        let end = if bucket == self.header.n_buckets() - 1 {
             self.n_hash_items()
         } else {
             min(
@@ -317,32 +407,338 @@ impl<'a> GvdbHashTable<'a> {
             ) as usize
         };
 
-        while itemno < lastno {
-            let item = self.get_hash_item_for_index(itemno)?;
-            if hash_value == item.hash_value() && self.check_name(&item, key) {
-                return Ok(item);
+        Ok(start..end)
+    }
+
+    /// Check whether the item at `index` has the key `key` and the hash value `hash_value`
+    pub fn check_hash_item(
+        &self,
+        index: usize,
+        key: &str,
+        hash_value: u32,
+        get_key: &impl Fn(&GvdbHashItem) -> GvdbReaderResult<String>,
+    ) -> Option<GvdbHashItem> {
+        if index >= self.n_hash_items() {
+            return None;
+        }
+
+        let item = self.get_hash_item_for_index(index).ok()?;
+        if item.hash_value() == hash_value && self.check_name(&item, key, get_key) {
+            Some(item)
+        } else {
+            None
+        }
+    }
+}
+
+/// The error for an item that is its own parent, directly or through other items
+fn parent_loop_error() -> GvdbReaderError {
+    GvdbReaderError::DataError(
+        "Error finding all parent items. The file appears to have a loop".to_string(),
+    )
+}
+
+/// A hash table inside a GVDB file
+///
+///
+#[repr(C)]
+#[derive(Clone, Debug)]
+pub struct GvdbHashTable<'a> {
+    pub(crate) root: &'a GvdbFile<'a>,
+    pub(crate) table: GvdbHashTableData<'a>,
+    path: Cow<'a, [String]>,
+}
+
+impl<'a> GvdbHashTable<'a> {
+    /// Interpret a chunk of bytes as a HashTable. The table_ptr should point to the hash table.
+    /// Data has to be the complete GVDB file, as hash table items are stored somewhere else.
+    pub fn for_bytes(data: &'a [u8], root: &'a GvdbFile<'a>) -> GvdbReaderResult<Self> {
+        let table = GvdbHashTableData::for_bytes(Cow::Borrowed(data))?;
+        root.limits.check_items(table.n_hash_items())?;
+        Ok(Self {
+            root,
+            table,
+            path: Cow::Borrowed(&[]),
+        })
+    }
+
+    /// Create a hash table for `data` whose `header` was already validated by
+    /// [`for_bytes()`](Self::for_bytes)
+    #[cfg(feature = "std")]
+    pub(crate) fn for_validated_bytes(
+        data: &'a [u8],
+        header: GvdbHashHeader,
+        root: &'a GvdbFile<'a>,
+    ) -> Self {
+        Self {
+            root,
+            table: GvdbHashTableData {
+                data: Cow::Borrowed(data),
+                header,
+            },
+            path: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// Set the keys of the items that lead to this hash table
+    pub(crate) fn with_path(mut self, path: impl Into<Cow<'a, [String]>>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// The path of a hash table that is stored in this table at key `key`. Fails if the
+    /// table would be nested deeper than the limits of the file allow.
+    pub(crate) fn child_path(&self, key: &str) -> GvdbReaderResult<Vec<String>> {
+        self.root.limits.check_depth(self.path.len() + 1)?;
+        let mut path = self.path.to_vec();
+        path.push(key.to_string());
+        Ok(path)
+    }
+
+    /// The keys of the items that lead from the root hash table of the file to this hash table.
+    /// Empty for the root table and for tables created with [`for_bytes()`](Self::for_bytes).
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Add the path of this table and the index of the item the error occurred at to the
+    /// context of an error
+    pub(crate) fn error_context(
+        &self,
+        item_index: Option<usize>,
+    ) -> impl Fn(GvdbReaderError) -> GvdbReaderError + '_ {
+        move |err| err.with_item(&self.path, item_index)
+    }
+
+    /// Returns the header for this hash table
+    pub fn get_header(&self) -> GvdbHashHeader {
+        self.table.header()
+    }
+
+    /// Gets a list of keys contained in the hash table, in the order the items are stored in the
+    /// file
+    pub fn get_names(&self) -> GvdbReaderResult<Vec<String>> {
+        self.table
+            .get_names(|item| self.get_key(item))
+            .map_err(self.error_context(None))
+    }
+
+    /// Gets the item at key `key`
+    pub fn get_hash_item(&self, key: &str) -> GvdbReaderResult<GvdbHashItem> {
+        self.find_hash_item(key).map(|(_, item)| item)
+    }
+
+    /// Gets the index of the item at key `key` and the item itself
+    pub(crate) fn find_hash_item(&self, key: &str) -> GvdbReaderResult<(usize, GvdbHashItem)> {
+        self.root
+            .limits
+            .check_key_length(key.len())
+            .map_err(self.error_context(None))?;
+        self.table
+            .find_hash_item(key, |item| self.get_key(item))
+            .map_err(self.error_context(None))
+    }
+
+    /// Whether the hash table contains an item with the key `key`. Returns `false` if the item
+    /// can't be read.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get_hash_item(key).is_ok()
+    }
+
+    /// Get information about the item at key `key`, without decoding its value
+    pub fn get_item_info(&self, key: &str) -> GvdbReaderResult<GvdbItemInfo<'a>> {
+        let (index, item) = self.find_hash_item(key)?;
+        self.item_info(key, index, &item)
+    }
+
+    /// The offset of this hash table in the file, to remember where a [`GvdbKey`] was found
+    fn key_hint_offset(&self) -> Option<u32> {
+        self.root
+            .offset_of(self.table.data())
+            .and_then(|offset| u32::try_from(offset).ok())
+    }
+
+    /// The item the key `key` was found at the last time, if it still has this key
+    fn hinted_hash_item(
+        &self,
+        key: &GvdbKey,
+        hint_offset: Option<u32>,
+    ) -> Option<(usize, GvdbHashItem)> {
+        let index = key.hint(hint_offset?)?;
+        let item = self.table.check_hash_item(
+            index,
+            key.as_str(),
+            key.hash_value(),
+            &|item: &GvdbHashItem| self.get_key(item),
+        )?;
+        Some((index, item))
+    }
+
+    /// Look up the item at the key `key`, checking the item it was found at the last time first
+    fn find_hash_item_for_key(
+        &self,
+        key: &GvdbKey,
+        hint_offset: Option<u32>,
+    ) -> GvdbReaderResult<(usize, GvdbHashItem)> {
+        self.root
+            .limits
+            .check_key_length(key.as_str().len())
+            .map_err(self.error_context(None))?;
+        if let Some(hinted) = self.hinted_hash_item(key, hint_offset) {
+            return Ok(hinted);
+        }
+
+        let get_key = |item: &GvdbHashItem| self.get_key(item);
+
+        let (index, item) = self
+            .table
+            .find_hash_item_with_hash(key.as_str(), key.hash_value(), &get_key)
+            .map_err(self.error_context(None))?;
+        if let Some(offset) = hint_offset {
+            key.set_hint(offset, index);
+        }
+
+        Ok((index, item))
+    }
+
+    /// Get information about the item at the precomputed key `key`, without decoding its value
+    ///
+    /// See [`GvdbKey`] for why this is faster than [`get_item_info()`](Self::get_item_info) for
+    /// keys that are looked up repeatedly.
+    pub fn get_item_info_for_key(&self, key: &GvdbKey) -> GvdbReaderResult<GvdbItemInfo<'a>> {
+        let (index, item) = self.find_hash_item_for_key(key, self.key_hint_offset())?;
+        self.item_info(key.as_str(), index, &item)
+    }
+
+    /// Get information about the items at all keys in `keys`, in the same order
+    ///
+    /// Keys that are not found where they were found the last time are looked up bucket by
+    /// bucket, so that the items in each hash bucket are only read once, no matter how many of
+    /// the keys belong to the bucket.
+    ///
+    /// ```
+    /// # use gvdb::read::{GvdbFile, GvdbKey};
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let table = file.hash_table().unwrap();
+    ///
+    /// let keys = [
+    ///     GvdbKey::new("/gvdb/rs/test/test.css"),
+    ///     GvdbKey::new("/gvdb/rs/test/missing"),
+    ///     GvdbKey::new("/gvdb/rs/test/json/test.json"),
+    /// ];
+    /// let infos = table.get_many(&keys);
+    /// assert_eq!(infos[0].as_ref().unwrap().key(), "/gvdb/rs/test/test.css");
+    /// assert!(infos[1].is_err());
+    /// assert_eq!(infos[2].as_ref().unwrap().key(), "/gvdb/rs/test/json/test.json");
+    /// ```
+    pub fn get_many(&self, keys: &[GvdbKey]) -> Vec<GvdbReaderResult<GvdbItemInfo<'a>>> {
+        let hint_offset = self.key_hint_offset();
+        let get_key = |item: &GvdbHashItem| self.get_key(item);
+        let mut found: Vec<Option<GvdbReaderResult<(usize, GvdbHashItem)>>> =
+            keys.iter().map(|_| None).collect();
+
+        // Sort the keys that don't have a valid hint by their hash bucket
+        let mut pending = Vec::new();
+        for (key_index, key) in keys.iter().enumerate() {
+            // Reject keys that are too long before doing any work for them
+            if let Err(err) = self.root.limits.check_key_length(key.as_str().len()) {
+                found[key_index] = Some(Err(self.error_context(None)(err)));
+                continue;
             }
 
-            itemno += 1;
+            match self.hinted_hash_item(key, hint_offset) {
+                Some(hinted) => found[key_index] = Some(Ok(hinted)),
+                None => match self.table.bucket_items(key.hash_value()) {
+                    Ok(bucket) if !bucket.is_empty() => {
+                        pending.push((bucket.start, bucket.end, key_index))
+                    }
+                    _ => {}
+                },
+            }
         }
+        pending.sort_unstable();
+
+        let mut bucket_items = Vec::new();
+        let mut current_bucket = None;
+        for (start, end, key_index) in pending {
+            if current_bucket != Some(start) {
+                current_bucket = Some(start);
+                bucket_items = (start..end)
+                    .filter_map(|index| {
+                        let item = self.table.get_hash_item_for_index(index).ok()?;
+                        Some((index, item))
+                    })
+                    .collect();
+            }
 
-        Err(GvdbReaderError::KeyError(key.to_string()))
+            let key = &keys[key_index];
+            found[key_index] = bucket_items
+                .iter()
+                .find(|(_, item)| {
+                    item.hash_value() == key.hash_value()
+                        && self.table.check_name(item, key.as_str(), &get_key)
+                })
+                .copied()
+                .map(Ok);
+
+            if let (Some(offset), Some(Ok((index, _)))) = (hint_offset, &found[key_index]) {
+                key.set_hint(offset, *index);
+            }
+        }
+
+        keys.iter()
+            .zip(found)
+            .map(|(key, found)| match found {
+                Some(Ok((index, item))) => self.item_info(key.as_str(), index, &item),
+                Some(Err(err)) => Err(err),
+                // Look up the key again to get the exact error
+                None => self
+                    .find_hash_item_for_key(key, None)
+                    .and_then(|(index, item)| self.item_info(key.as_str(), index, &item)),
+            })
+            .collect()
+    }
+
+    pub(crate) fn item_info(
+        &self,
+        key: &str,
+        index: usize,
+        item: &GvdbHashItem,
+    ) -> GvdbReaderResult<GvdbItemInfo<'a>> {
+        let typ = item.typ().map_err(self.error_context(Some(index)))?;
+        let alignment = match typ {
+            GvdbHashItemType::Value => 8,
+            GvdbHashItemType::HashTable | GvdbHashItemType::Container => 4,
+        };
+
+        let data = self
+            .root
+            .dereference(item.value_ptr(), alignment)
+            .map_err(self.error_context(Some(index)))?;
+        Ok(GvdbItemInfo::new(
+            key.to_string(),
+            typ,
+            data,
+            self.root.byteswapped,
+        ))
     }
 
     /// Get the item at key `key` and try to interpret it as a [`enum@zvariant::Value`]
+    #[cfg(feature = "std")]
     pub fn get_value(&self, key: &str) -> GvdbReaderResult<zvariant::Value> {
-        self.get_value_for_item(&self.get_hash_item(key)?)
+        let (index, item) = self.find_hash_item(key)?;
+        self.get_value_for_item(&item)
+            .map_err(self.error_context(Some(index)))
     }
 
     /// Get the item at key `key` and try to convert it from [`enum@zvariant::Value`] to T
+    #[cfg(feature = "std")]
     pub fn get<T: ?Sized>(&self, key: &str) -> GvdbReaderResult<T>
     where
         T: TryFrom<zvariant::OwnedValue>,
     {
-        T::try_from(zvariant::OwnedValue::from(
-            self.get_value_for_item(&self.get_hash_item(key)?)?,
-        ))
-        .map_err(|_| {
+        T::try_from(zvariant::OwnedValue::from(self.get_value(key)?)).map_err(|_| {
             GvdbReaderError::DataError("Can't convert Value to specified type".to_string())
         })
     }
@@ -350,18 +746,114 @@ impl<'a> GvdbHashTable<'a> {
     #[cfg(feature = "glib")]
     /// Get the item at key `key` and try to interpret it as a [`struct@glib::Variant`]
     pub fn get_gvariant(&self, key: &str) -> GvdbReaderResult<glib::Variant> {
-        self.get_gvariant_for_item(&self.get_hash_item(key)?)
+        let (index, item) = self.find_hash_item(key)?;
+        self.get_gvariant_for_item(&item)
+            .map_err(self.error_context(Some(index)))
+    }
+
+    #[cfg(feature = "glib")]
+    /// Get the item at key `key` and convert the [`struct@glib::Variant`] inside of it to `T`
+    ///
+    /// ```
+    /// # use gvdb::read::GvdbFile;
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let table = file.hash_table().unwrap().get_hash_table("table").unwrap();
+    /// let int: u32 = table.get_as_variant("int").unwrap();
+    /// assert_eq!(int, 42);
+    /// ```
+    pub fn get_as_variant<T: glib::FromVariant>(&self, key: &str) -> GvdbReaderResult<T> {
+        let variant = self.get_gvariant(key)?;
+        variant
+            .as_variant()
+            .and_then(|variant| variant.get())
+            .ok_or_else(|| {
+                GvdbReaderError::DataError(format!(
+                    "Can't convert GVariant at key '{}' to type '{}'",
+                    key,
+                    T::static_variant_type()
+                ))
+                .with_item(&self.path, None)
+            })
     }
 
     /// Get the item at key `key` and try to interpret it as a [`GvdbHashTable`]
     pub fn get_hash_table(&self, key: &str) -> GvdbReaderResult<GvdbHashTable> {
-        self.get_hash_table_for_item(&self.get_hash_item(key)?)
+        let (index, item) = self.find_hash_item(key)?;
+        let path = self
+            .child_path(key)
+            .map_err(self.error_context(Some(index)))?;
+        Ok(self
+            .get_hash_table_for_item(&item)
+            .map_err(self.error_context(Some(index)))?
+            .with_path(path))
+    }
+
+    /// Advise the kernel that the structure and the keys of this hash table will be needed soon,
+    /// to avoid page faults during lookups. This only has an effect on memory-mapped files.
+    pub fn prefetch_table(&self) -> GvdbReaderResult<()> {
+        self.prefetch_table_data().map_err(self.error_context(None))
+    }
+
+    fn prefetch_table_data(&self) -> GvdbReaderResult<()> {
+        let data = self.table.data();
+        if let Some(start) = self.root.offset_of(data) {
+            self.root.prefetch_range(start, start + data.len())?;
+        }
+
+        // Keys are stored in one block by the writer, so prefetch the span they cover
+        let mut keys: Option<(usize, usize)> = None;
+        for index in 0..self.table.n_hash_items() {
+            let key_ptr = self.table.get_hash_item_for_index(index)?.key_ptr();
+            let (start, end) = (key_ptr.start() as usize, key_ptr.end() as usize);
+            keys = Some(match keys {
+                Some((min, max)) => (min.min(start), max.max(end)),
+                None => (start, end),
+            });
+        }
+
+        if let Some((start, end)) = keys {
+            self.root.prefetch_range(start, end)?;
+        }
+
+        Ok(())
+    }
+
+    /// Advise the kernel that the data of the item at key `key` will be needed soon. The key has
+    /// to be looked up for this, so the hash table structure is accessed immediately. This only
+    /// has an effect on memory-mapped files.
+    pub fn prefetch(&self, key: &str) -> GvdbReaderResult<()> {
+        let (index, item) = self.find_hash_item(key)?;
+        let value_ptr = item.value_ptr();
+        self.root
+            .prefetch_range(value_ptr.start() as usize, value_ptr.end() as usize)
+            .map_err(self.error_context(Some(index)))
     }
 
     fn get_key(&self, item: &GvdbHashItem) -> GvdbReaderResult<String> {
         self.root.get_key(item)
     }
 
+    /// Get the key of `item` including the keys of all of its parents
+    #[cfg(feature = "std")]
+    pub(crate) fn get_full_key(&self, item: &GvdbHashItem) -> GvdbReaderResult<String> {
+        let mut key = self.get_key(item)?;
+        let mut parent = item.parent();
+        // Every item can only appear once in the chain of parents, unless there is a loop
+        for _ in 0..self.table.n_hash_items() {
+            if parent == u32::MAX {
+                return Ok(key);
+            }
+
+            let parent_item = self.table.get_hash_item_for_index(parent as usize)?;
+            key = self.get_key(&parent_item)? + &key;
+            parent = parent_item.parent();
+        }
+
+        Err(parent_loop_error())
+    }
+
+    #[cfg(feature = "std")]
     fn get_value_for_item(&self, item: &GvdbHashItem) -> GvdbReaderResult<zvariant::Value> {
         self.root.get_value_for_item(item)
     }
@@ -376,11 +868,16 @@ impl<'a> GvdbHashTable<'a> {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(feature = "std", test))]
 pub(crate) mod test {
-    use crate::read::{GvdbFile, GvdbHashHeader, GvdbPointer, GvdbReaderError};
+    use crate::read::{
+        GvdbFile, GvdbHashHeader, GvdbHashItemType, GvdbKey, GvdbPointer, GvdbReaderError,
+    };
     use crate::test::*;
     use crate::test::{assert_eq, assert_matches, assert_ne};
+    use crate::util::djb_hash;
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::borrow::Cow;
 
     #[test]
     fn derives() {
@@ -414,7 +911,78 @@ pub(crate) mod test {
         let header = table.get_header();
         assert_eq!(header.n_bloom_words(), 0);
         assert_eq!(header.bloom_words_len(), 0);
-        assert_eq!(table.bloom_words(), None);
+        assert_eq!(table.table.bloom_words(), None);
+    }
+
+    #[test]
+    fn get_names_nested() {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        let mut key = String::new();
+        for num in 0..500 {
+            key += &format!("{}/", num);
+            table_builder.insert(&format!("{}value", key), num).unwrap();
+        }
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+
+        // 500 values and 500 containers
+        let names = table.get_names().unwrap();
+        assert_eq!(names.len(), 1000);
+        assert_eq!(names, table.get_names().unwrap());
+        for (index, name) in names.iter().enumerate() {
+            let (found_index, _) = table.find_hash_item(name).unwrap();
+            assert_eq!(found_index, index);
+        }
+
+        assert!(names.contains(&format!("{}value", key)));
+    }
+
+    /// Change the parent of the item at `index` in the root hash table of a file
+    fn set_parent(file: &GvdbFile, index: usize, parent: u32) -> GvdbFile<'static> {
+        let table = file.hash_table().unwrap();
+        let offset = file.offset_of(table.table.data()).unwrap()
+            + table.table.hash_items_offset()
+            + index * std::mem::size_of::<crate::read::GvdbHashItem>()
+            + 4;
+
+        let mut data = file.data.as_ref().to_vec();
+        data[offset..offset + 4].copy_from_slice(&parent.to_le_bytes());
+        GvdbFile::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn get_names_invalid_parent() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let n_items = file.hash_table().unwrap().table.n_hash_items();
+        let item = file
+            .hash_table()
+            .unwrap()
+            .find_hash_item("/gvdb/")
+            .unwrap()
+            .0;
+
+        // An item that is its own parent
+        let broken = set_parent(&file, item, item as u32);
+        let err = broken.hash_table().unwrap().get_names().unwrap_err();
+        assert!(format!("{}", err).contains("appears to have a loop"));
+        assert!(format!("{}", err).contains(&format!("item {}", item)));
+
+        // Two items that are each other's parent
+        let parent = file.hash_table().unwrap().find_hash_item("/").unwrap().0;
+        let broken = set_parent(&set_parent(&file, parent, item as u32), item, parent as u32);
+        let err = broken.hash_table().unwrap().get_names().unwrap_err();
+        assert!(format!("{}", err).contains("appears to have a loop"));
+
+        for invalid in [n_items as u32, u32::MAX - 1] {
+            let broken = set_parent(&file, item, invalid);
+            let err = broken.hash_table().unwrap().get_names().unwrap_err();
+            assert_matches!(err.inner(), GvdbReaderError::DataError(msg) if msg.contains("invalid offset"));
+            assert_eq!(err.context().unwrap().item_index(), Some(item));
+        }
     }
 
     #[test]
@@ -458,7 +1026,7 @@ pub(crate) mod test {
         for endianess in [true, false] {
             let file = new_simple_file(endianess);
             let table = file.hash_table().unwrap();
-            let res = table.get_bloom_word(0);
+            let res = table.table.get_bloom_word(0);
             assert_matches!(res, Err(GvdbReaderError::DataOffset));
         }
     }
@@ -468,8 +1036,60 @@ pub(crate) mod test {
         for endianess in [true, false] {
             let file = new_simple_file(endianess);
             let table = file.hash_table().unwrap();
-            let res = table.bloom_shift();
-            assert_eq!(res, 0);
+            let res = table.table.bloom_shift();
+            assert_eq!(res, 5);
+        }
+    }
+
+    #[test]
+    fn bloom_filter() {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.set_bloom_filter(4, 6).unwrap();
+        for num in 0..50 {
+            table_builder
+                .insert_string(&format!("{}", num), "test")
+                .unwrap();
+        }
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_header().n_bloom_words(), 4);
+        assert_eq!(table.get_header().bloom_shift(), 6);
+        assert_eq!(table.table.bloom_words().unwrap().len(), 4);
+
+        for num in 0..50 {
+            assert!(table.table.bloom_filter(djb_hash(&format!("{}", num))));
+            let value: String = table.get(&format!("{}", num)).unwrap();
+            assert_eq!(value, "test");
+        }
+
+        assert_matches!(
+            table.get_hash_item("fail"),
+            Err(GvdbReaderError::KeyError(_))
+        );
+    }
+
+    #[test]
+    fn hash_table_layout() {
+        let mut table_builder = GvdbHashTableBuilder::with_path_separator(None);
+        table_builder.set_load_factor(3.0).unwrap();
+        for num in 0..30 {
+            table_builder.insert(&format!("{}", num), num).unwrap();
+        }
+        assert_eq!(table_builder.n_buckets(), 10);
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_header().n_buckets(), 10);
+        for num in 0..30 {
+            let value: i32 = table.get(&format!("{}", num)).unwrap();
+            assert_eq!(value, num);
         }
     }
 
@@ -491,8 +1111,170 @@ pub(crate) mod test {
         let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
         let table = file.hash_table().unwrap();
         let table = table.get_hash_table("table").unwrap();
+        assert_eq!(table.path(), ["table"]);
         let fail = table.get_hash_table("fail").unwrap_err();
-        assert_matches!(fail, GvdbReaderError::KeyError(_));
+        assert_matches!(fail.inner(), GvdbReaderError::KeyError(_));
+        assert_eq!(fail.context().unwrap().table_path(), ["table"]);
+    }
+
+    #[test]
+    fn item_info() {
+        let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        assert!(table.contains_key("string"));
+        assert!(!table.contains_key("int"));
+        assert_matches!(
+            table.get_item_info("fail"),
+            Err(GvdbReaderError::KeyError(_))
+        );
+
+        let info = table.get_item_info("table").unwrap();
+        assert_eq!(info.key(), "table");
+        assert_eq!(info.typ(), GvdbHashItemType::HashTable);
+        let sub_table = table.get_hash_table("table").unwrap();
+        assert_eq!(info.size(), sub_table.table.data().len());
+
+        let info = sub_table.get_item_info("int").unwrap();
+        assert_eq!(info.signature(), Some("u"));
+        // This file is big endian
+        assert_eq!(info.value_bytes(), Some(&42u32.to_be_bytes()[..]));
+        assert_eq!(info.size(), 6);
+        assert_eq!(info.is_byteswapped(), cfg!(target_endian = "little"));
+
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        let info = table.get_item_info("/gvdb/rs/test/").unwrap();
+        assert_eq!(info.typ(), GvdbHashItemType::Container);
+        let info = table.get_item_info("/gvdb/rs/test/json/test.json").unwrap();
+        assert_eq!(info.signature(), Some("(uuay)"));
+
+        let file = new_simple_file(true);
+        let info = file.hash_table().unwrap().get_item_info("test").unwrap();
+        assert_eq!(info.signature(), Some("s"));
+        assert_eq!(info.value_bytes(), Some(&b"test\0"[..]));
+        assert_eq!(info.is_byteswapped(), cfg!(target_endian = "little"));
+    }
+
+    fn numbered_file(n_items: usize, n_buckets: usize, prefix: &str) -> GvdbFile<'static> {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.set_n_buckets(Some(n_buckets)).unwrap();
+        for num in 0..n_items {
+            table_builder
+                .insert(&format!("{}{}", prefix, num), num as u32)
+                .unwrap();
+        }
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        GvdbFile::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn get_item_info_for_key() {
+        let file = numbered_file(50, 4, "");
+        let table = file.hash_table().unwrap();
+
+        for num in 0..50 {
+            let key = GvdbKey::new(format!("{}", num));
+            let info = table.get_item_info_for_key(&key).unwrap();
+            assert_eq!(info.key(), key.as_str());
+            assert_eq!(
+                info.bytes(),
+                table.get_item_info(key.as_str()).unwrap().bytes()
+            );
+
+            // The second lookup uses the hint
+            let (index, _) = table.find_hash_item(key.as_str()).unwrap();
+            let offset = table.key_hint_offset().unwrap();
+            assert_eq!(key.hint(offset), Some(index));
+            let info = table.get_item_info_for_key(&key).unwrap();
+            assert_eq!(info.key(), key.as_str());
+        }
+
+        let err = table
+            .get_item_info_for_key(&GvdbKey::new("fail"))
+            .unwrap_err();
+        assert_matches!(err, GvdbReaderError::KeyError(_));
+
+        // A hint for another table at the same offset is wrong, but still works
+        let key = GvdbKey::new("7");
+        table.get_item_info_for_key(&key).unwrap();
+        let other_file = numbered_file(50, 4, "other");
+        let other_table = other_file.hash_table().unwrap();
+        assert_eq!(other_table.key_hint_offset(), table.key_hint_offset());
+        assert_matches!(
+            other_table.get_item_info_for_key(&key),
+            Err(GvdbReaderError::KeyError(_))
+        );
+        let other_key = GvdbKey::new("other7");
+        other_key.set_hint(other_table.key_hint_offset().unwrap(), 0);
+        let info = other_table.get_item_info_for_key(&other_key).unwrap();
+        assert_eq!(info.key(), "other7");
+    }
+
+    #[test]
+    fn get_many() {
+        let file = numbered_file(100, 7, "");
+        let table = file.hash_table().unwrap();
+
+        let keys: Vec<GvdbKey> = (0..120)
+            .rev()
+            .map(|num| GvdbKey::new(format!("{}", num)))
+            .collect();
+
+        // The first round has no hints, the second round uses them
+        for _ in 0..2 {
+            let infos = table.get_many(&keys);
+            assert_eq!(infos.len(), keys.len());
+            for (key, info) in keys.iter().zip(infos) {
+                let num: usize = key.as_str().parse().unwrap();
+                if num < 100 {
+                    let info = info.unwrap();
+                    assert_eq!(info.key(), key.as_str());
+                    let expected = table.get_item_info(key.as_str()).unwrap();
+                    assert_eq!(info.bytes(), expected.bytes());
+                } else {
+                    assert_matches!(info, Err(GvdbReaderError::KeyError(_)));
+                }
+            }
+        }
+
+        assert!(table.get_many(&[]).is_empty());
+        let empty = new_empty_file();
+        let infos = empty.hash_table().unwrap().get_many(&keys[..2]);
+        assert_matches!(infos[0], Err(GvdbReaderError::KeyError(_)));
+    }
+
+    #[test]
+    fn prefetch() {
+        // Without mmap this does nothing
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        table.prefetch_table().unwrap();
+        table.prefetch("/gvdb/rs/test/json/test.json").unwrap();
+        assert_matches!(table.prefetch("fail"), Err(GvdbReaderError::KeyError(_)));
+
+        let file = new_empty_file();
+        file.hash_table().unwrap().prefetch_table().unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn prefetch_mmap() {
+        let file = unsafe { GvdbFile::from_file_mmap(&TEST_FILE_3).unwrap() };
+        let table = file.hash_table().unwrap();
+        table.prefetch_table().unwrap();
+        table.prefetch("/gvdb/rs/test/json/test.json").unwrap();
+        assert_matches!(table.prefetch("fail"), Err(GvdbReaderError::KeyError(_)));
+        assert_is_file_3(&file);
+
+        let file = unsafe { GvdbFile::from_file_mmap(&TEST_FILE_2).unwrap() };
+        let table = file.hash_table().unwrap();
+        table.prefetch("table").unwrap();
+        let sub_table = table.get_hash_table("table").unwrap();
+        sub_table.prefetch_table().unwrap();
+        sub_table.prefetch("int").unwrap();
     }
 }
 
@@ -515,4 +1297,32 @@ mod test_glib {
             assert_matches!(fail, GvdbReaderError::KeyError(_));
         }
     }
+
+    #[test]
+    fn get_as_variant() {
+        for endianess in [true, false] {
+            let file = new_simple_file(endianess);
+            let table = file.hash_table().unwrap();
+            let res: String = table.get_as_variant("test").unwrap();
+            assert_eq!(res, "test");
+
+            let err = table.get_as_variant::<u32>("test").unwrap_err();
+            assert_matches!(err, GvdbReaderError::DataError(_));
+        }
+    }
+
+    #[test]
+    fn from_glib_bytes() {
+        let data = std::fs::read(&*crate::test::TEST_FILE_3).unwrap();
+        let bytes = glib::Bytes::from_owned(data);
+        let file = crate::read::GvdbFile::from_glib_bytes(bytes.clone()).unwrap();
+        let table = file.hash_table().unwrap();
+
+        // The variant shares the data of the file
+        let variant = table.get_gvariant("/gvdb/rs/test/test.css").unwrap();
+        let data = variant.data();
+        let start = data.as_ptr() as usize - bytes.as_ptr() as usize;
+        assert!(start + data.len() <= bytes.len());
+        assert_eq!(&bytes[start..start + data.len()], data);
+    }
 }
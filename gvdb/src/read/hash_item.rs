@@ -1,12 +1,21 @@
 use crate::read::error::{GvdbReaderError, GvdbReaderResult};
 use crate::read::pointer::GvdbPointer;
+#[cfg(feature = "std")]
+use alloc::format;
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
 use safe_transmute::TriviallyTransmutable;
-use std::fmt::{Display, Formatter};
 
-#[derive(PartialEq, Eq, Debug)]
+/// The type of an item in a GVDB hash table
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum GvdbHashItemType {
+    /// A GVariant value, stored with type `v` (`'v'`)
     Value,
+
+    /// A nested hash table (`'H'`)
     HashTable,
+
+    /// A container for the items below a path separator (`'L'`)
     Container,
 }
 
@@ -38,7 +47,7 @@ impl TryFrom<u8> for GvdbHashItemType {
 }
 
 impl Display for GvdbHashItemType {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         let text = match self {
             GvdbHashItemType::Value => "Value",
             GvdbHashItemType::HashTable => "HashTable",
@@ -123,9 +132,121 @@ impl GvdbHashItem {
     }
 }
 
-#[cfg(test)]
+/// Information about an item in a hash table, without decoding its value
+///
+/// The data is borrowed from the file, so values can be passed on without copying them.
+///
+/// ```
+/// # use gvdb::read::{GvdbFile, GvdbHashItemType};
+/// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+/// let file = GvdbFile::from_file(&path).unwrap();
+/// let table = file.hash_table().unwrap();
+///
+/// let info = table.get_item_info("string").unwrap();
+/// assert_eq!(info.typ(), GvdbHashItemType::Value);
+/// assert_eq!(info.signature(), Some("s"));
+/// assert_eq!(info.value_bytes(), Some(&b"test string\0"[..]));
+///
+/// let info = table.get_item_info("table").unwrap();
+/// assert_eq!(info.typ(), GvdbHashItemType::HashTable);
+/// assert_eq!(info.signature(), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GvdbItemInfo<'a> {
+    key: String,
+    typ: GvdbHashItemType,
+    data: &'a [u8],
+    byteswapped: bool,
+}
+
+impl<'a> GvdbItemInfo<'a> {
+    pub(crate) fn new(
+        key: String,
+        typ: GvdbHashItemType,
+        data: &'a [u8],
+        byteswapped: bool,
+    ) -> Self {
+        Self {
+            key,
+            typ,
+            data,
+            byteswapped,
+        }
+    }
+
+    /// The full key of the item
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The type of the item
+    pub fn typ(&self) -> GvdbHashItemType {
+        self.typ
+    }
+
+    /// The serialized data of the item as it is stored in the file
+    ///
+    /// For values this is a GVariant of type `v`, which includes the type signature. For hash
+    /// tables this is the hash table structure, for containers the list of child item indices.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The length of the serialized data of the item in bytes
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the value data is stored in the opposite byte order of this machine
+    pub fn is_byteswapped(&self) -> bool {
+        self.byteswapped
+    }
+
+    /// Split the `v` data into value and signature, at the last zero byte
+    fn split_variant(&self) -> Option<(&'a [u8], &'a str)> {
+        if self.typ != GvdbHashItemType::Value {
+            return None;
+        }
+
+        let separator = self.data.iter().rposition(|&b| b == 0)?;
+        let signature = core::str::from_utf8(&self.data[separator + 1..]).ok()?;
+        if signature.is_empty() {
+            None
+        } else {
+            Some((&self.data[..separator], signature))
+        }
+    }
+
+    /// The GVariant type signature of a value, read from the end of the serialized data.
+    /// Returns `None` for other item types or if the data is invalid.
+    pub fn signature(&self) -> Option<&'a str> {
+        self.split_variant().map(|(_, signature)| signature)
+    }
+
+    /// The serialized data of a value without the type signature, in the byte order of the file.
+    /// Returns `None` for other item types or if the data is invalid.
+    pub fn value_bytes(&self) -> Option<&'a [u8]> {
+        self.split_variant().map(|(value, _)| value)
+    }
+
+    /// Decode the value, taking the byte order of the file into account. Fails for other item
+    /// types.
+    #[cfg(feature = "std")]
+    pub fn value(&self) -> GvdbReaderResult<zvariant::Value<'a>> {
+        if self.typ != GvdbHashItemType::Value {
+            return Err(GvdbReaderError::DataError(format!(
+                "Unable to parse item for key '{}' as GVariant: Expected type 'v', got type {}",
+                self.key, self.typ
+            )));
+        }
+
+        crate::read::GvdbFile::parse_value(self.data, self.byteswapped)
+    }
+}
+
+#[cfg(all(feature = "std", test))]
 mod test {
-    use crate::read::{GvdbHashItem, GvdbHashItemType, GvdbPointer, GvdbReaderError};
+    use crate::read::{GvdbHashItem, GvdbHashItemType, GvdbItemInfo, GvdbPointer, GvdbReaderError};
     use matches::assert_matches;
 
     #[test]
@@ -188,4 +309,32 @@ mod test {
         assert_matches!(item.typ(), Ok(GvdbHashItemType::Value));
         assert_eq!(item.value_ptr(), &GvdbPointer::NULL);
     }
+
+    #[test]
+    fn item_info() {
+        let info = GvdbItemInfo::new("key".to_string(), GvdbHashItemType::Value, b"\x05\0u", true);
+        assert_eq!(info.key(), "key");
+        assert_eq!(info.size(), 3);
+        assert!(info.is_byteswapped());
+        assert_eq!(info.signature(), Some("u"));
+        assert_eq!(info.value_bytes(), Some(&b"\x05"[..]));
+        println!("{:?}", info.clone());
+
+        // Invalid data
+        let info = GvdbItemInfo::new("key".to_string(), GvdbHashItemType::Value, b"u", false);
+        assert_eq!(info.signature(), None);
+        let info = GvdbItemInfo::new("key".to_string(), GvdbHashItemType::Value, b"u\0", false);
+        assert_eq!(info.signature(), None);
+        assert_eq!(info.value_bytes(), None);
+
+        let info = GvdbItemInfo::new(
+            "key".to_string(),
+            GvdbHashItemType::Container,
+            b"\0u",
+            false,
+        );
+        assert_eq!(info.typ(), GvdbHashItemType::Container);
+        assert_eq!(info.signature(), None);
+        assert_eq!(info.bytes(), b"\0u");
+    }
 }
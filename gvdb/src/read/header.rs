@@ -21,6 +21,7 @@ pub struct GvdbHeader {
 unsafe impl TriviallyTransmutable for GvdbHeader {}
 
 impl GvdbHeader {
+    #[cfg(feature = "std")]
     pub fn new(byteswap: bool, version: u32, root: GvdbPointer) -> Self {
         let signature = if !byteswap {
             [GVDB_SIGNATURE0, GVDB_SIGNATURE1]
@@ -61,7 +62,7 @@ impl GvdbHeader {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(feature = "std", test))]
 mod test {
     use super::*;
     use safe_transmute::{transmute_one_pedantic, transmute_one_to_bytes};
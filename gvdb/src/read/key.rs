@@ -0,0 +1,140 @@
+use crate::util::djb_hash;
+use alloc::string::String;
+use core::fmt::{Debug, Formatter};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A key with a precomputed hash value, for looking up the same key many times
+///
+/// Lookups with a [`GvdbKey`] don't need to hash the key again. The key also remembers where the
+/// item was found the last time, and checks this item first on the next lookup in the same hash
+/// table, which skips scanning the hash bucket. The remembered location is only a hint: It is
+/// always verified, so a key can be used with any number of hash tables and files.
+///
+/// ```
+/// # use gvdb::read::{GvdbFile, GvdbKey};
+/// let path = std::path::PathBuf::from("test-data/test3.gresource");
+/// let file = GvdbFile::from_file(&path).unwrap();
+/// let table = file.hash_table().unwrap();
+///
+/// let key = GvdbKey::new("/gvdb/rs/test/test.css");
+/// for _ in 0..3 {
+///     let info = table.get_item_info_for_key(&key).unwrap();
+///     assert_eq!(info.signature(), Some("(uuay)"));
+/// }
+/// ```
+pub struct GvdbKey {
+    key: String,
+    hash_value: u32,
+    // The offset of the hash table in the file plus one, zero if there is no hint
+    hint_table: AtomicU32,
+    hint_index: AtomicU32,
+}
+
+impl GvdbKey {
+    /// Create a key and compute its hash value
+    pub fn new(key: impl Into<String>) -> Self {
+        let key = key.into();
+        let hash_value = djb_hash(&key);
+        Self {
+            key,
+            hash_value,
+            hint_table: AtomicU32::new(0),
+            hint_index: AtomicU32::new(0),
+        }
+    }
+
+    /// The key as string
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    /// The djb2 hash value of the key, as it is stored in GVDB files
+    pub fn hash_value(&self) -> u32 {
+        self.hash_value
+    }
+
+    /// The index of the item the key was found at the last time in the hash table at
+    /// `table_offset`
+    pub(crate) fn hint(&self, table_offset: u32) -> Option<usize> {
+        let index = self.hint_index.load(Ordering::Relaxed);
+        if self.hint_table.load(Ordering::Relaxed) == table_offset.wrapping_add(1) {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Remember that the key was found at item `index` in the hash table at `table_offset`
+    pub(crate) fn set_hint(&self, table_offset: u32, index: usize) {
+        if let Ok(index) = u32::try_from(index) {
+            self.hint_index.store(index, Ordering::Relaxed);
+            self.hint_table
+                .store(table_offset.wrapping_add(1), Ordering::Relaxed);
+        }
+    }
+}
+
+impl Clone for GvdbKey {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            hash_value: self.hash_value,
+            hint_table: AtomicU32::new(self.hint_table.load(Ordering::Relaxed)),
+            hint_index: AtomicU32::new(self.hint_index.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl Debug for GvdbKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("GvdbKey")
+            .field("key", &self.key)
+            .field("hash_value", &self.hash_value)
+            .finish()
+    }
+}
+
+impl PartialEq for GvdbKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for GvdbKey {}
+
+impl From<&str> for GvdbKey {
+    fn from(key: &str) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<String> for GvdbKey {
+    fn from(key: String) -> Self {
+        Self::new(key)
+    }
+}
+
+#[cfg(all(feature = "std", test))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hint() {
+        let key = GvdbKey::from("test");
+        assert_eq!(key.as_str(), "test");
+        assert_eq!(key.hash_value(), djb_hash("test"));
+        assert_eq!(key.hint(0), None);
+
+        key.set_hint(0, 5);
+        assert_eq!(key.hint(0), Some(5));
+        assert_eq!(key.hint(8), None);
+
+        let clone = key.clone();
+        assert_eq!(clone, key);
+        assert_eq!(clone.hint(0), Some(5));
+        key.set_hint(8, 1);
+        assert_eq!(key.hint(8), Some(1));
+        assert_eq!(clone.hint(0), Some(5));
+        assert!(format!("{:?}", key).contains("\"test\""));
+    }
+}
@@ -0,0 +1,475 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use crate::read::file::GvdbFile;
+use crate::read::hash::GvdbHashTableData;
+use crate::read::hash_item::{GvdbHashItem, GvdbHashItemType};
+use crate::read::header::GvdbHeader;
+use crate::read::limits::GvdbReaderLimits;
+use crate::read::pointer::GvdbPointer;
+use safe_transmute::transmute_one_pedantic;
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::mem::size_of;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+const DEFAULT_BLOCK_SIZE: usize = 4096;
+const DEFAULT_CACHE_BLOCKS: usize = 64;
+
+/// A small least-recently-used cache of fixed size blocks of the source
+struct BlockCache<R> {
+    source: R,
+    len: u64,
+    block_size: usize,
+    n_blocks: usize,
+    // Most recently used block first
+    blocks: VecDeque<(u64, Box<[u8]>)>,
+}
+
+impl<R: Read + Seek> BlockCache<R> {
+    fn new(mut source: R, block_size: usize, n_blocks: usize) -> std::io::Result<Self> {
+        let len = source.seek(SeekFrom::End(0))?;
+
+        Ok(Self {
+            source,
+            len,
+            block_size: block_size.max(1),
+            n_blocks,
+            blocks: VecDeque::with_capacity(n_blocks),
+        })
+    }
+
+    fn read_uncached(&mut self, start: u64, buf: &mut [u8]) -> std::io::Result<()> {
+        self.source.seek(SeekFrom::Start(start))?;
+        self.source.read_exact(buf)
+    }
+
+    fn block(&mut self, index: u64) -> std::io::Result<&[u8]> {
+        if let Some(position) = self.blocks.iter().position(|(i, _)| *i == index) {
+            let block = self.blocks.remove(position).unwrap();
+            self.blocks.push_front(block);
+        } else {
+            let start = index * self.block_size as u64;
+            let end = (start + self.block_size as u64).min(self.len);
+            let mut data = vec![0; (end - start) as usize].into_boxed_slice();
+            self.read_uncached(start, &mut data)?;
+
+            if self.blocks.len() >= self.n_blocks {
+                self.blocks.pop_back();
+            }
+            self.blocks.push_front((index, data));
+        }
+
+        Ok(&self.blocks[0].1)
+    }
+
+    /// Read the bytes from `start` to `end`. The range has to be inside the source.
+    fn read(&mut self, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+        let mut data = vec![0; (end - start) as usize];
+
+        // Large reads are typically values that are only needed once
+        if self.n_blocks == 0 || data.len() > self.block_size {
+            self.read_uncached(start, &mut data)?;
+            return Ok(data);
+        }
+
+        let block_size = self.block_size as u64;
+        let mut offset = start;
+        while offset < end {
+            let block_start = offset - offset % block_size;
+            let block = self.block(offset / block_size)?;
+            let from = (offset - block_start) as usize;
+            let to = ((end - block_start) as usize).min(block.len());
+            let dest = (offset - start) as usize;
+            data[dest..dest + to - from].copy_from_slice(&block[from..to]);
+            offset = block_start + to as u64;
+        }
+
+        Ok(data)
+    }
+}
+
+/// A GVDB file that is read on demand from a [`Read`] + [`Seek`] source
+///
+/// Only the header, the structure of the hash tables that are accessed, and the keys and values
+/// that are looked up are read from the source. Small reads go through a block cache, so
+/// repeated lookups in the same region don't hit the source again.
+///
+/// Values are returned as [`zvariant::OwnedValue`], as the data is not kept in memory.
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::read::LazyGvdbFile;
+///
+/// let file = LazyGvdbFile::open(&PathBuf::from("test-data/test3.gresource")).unwrap();
+/// let table = file.hash_table().unwrap();
+/// let value = table.get_value("/gvdb/rs/test/json/test.json").unwrap();
+/// ```
+pub struct LazyGvdbFile<R> {
+    cache: Mutex<BlockCache<R>>,
+    header: GvdbHeader,
+    byteswapped: bool,
+    limits: GvdbReaderLimits,
+}
+
+impl LazyGvdbFile<File> {
+    /// Open a file and read GVDB data from it on demand
+    pub fn open(filename: &Path) -> GvdbReaderResult<Self> {
+        let file =
+            File::open(filename).map_err(GvdbReaderError::from_io_with_filename(filename))?;
+        Self::new(file)
+    }
+}
+
+impl<R: Read + Seek> LazyGvdbFile<R> {
+    /// Read GVDB data from `source` on demand, using a default sized block cache
+    pub fn new(source: R) -> GvdbReaderResult<Self> {
+        Self::with_cache(source, DEFAULT_BLOCK_SIZE, DEFAULT_CACHE_BLOCKS)
+    }
+
+    /// Read GVDB data from `source` on demand, caching up to `n_blocks` blocks of `block_size`
+    /// bytes. Reads that are larger than one block are never cached.
+    pub fn with_cache(source: R, block_size: usize, n_blocks: usize) -> GvdbReaderResult<Self> {
+        let cache = BlockCache::new(source, block_size, n_blocks)
+            .map_err(|err| GvdbReaderError::Io(err, None))?;
+
+        let mut this = Self {
+            cache: Mutex::new(cache),
+            header: GvdbHeader::new(false, 0, GvdbPointer::NULL),
+            byteswapped: false,
+            limits: GvdbReaderLimits::default(),
+        };
+
+        let header_ptr = GvdbPointer::new(0, size_of::<GvdbHeader>());
+        this.header = transmute_one_pedantic(&this.read(&header_ptr, 1)?)?;
+        this.byteswapped = GvdbFile::check_header(&this.header)?;
+
+        Ok(this)
+    }
+
+    fn lock(&self) -> MutexGuard<BlockCache<R>> {
+        // The cache doesn't have any invariants that a panic while reading could break
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Read the data a pointer points to
+    fn read(&self, pointer: &GvdbPointer, alignment: u32) -> GvdbReaderResult<Vec<u8>> {
+        let start = pointer.start() as u64;
+        let end = pointer.end() as u64;
+        let mut cache = self.lock();
+
+        if start > end || end > cache.len {
+            Err(GvdbReaderError::DataOffset)
+        } else if start & (alignment as u64 - 1) != 0 {
+            Err(GvdbReaderError::DataAlignment)
+        } else {
+            cache
+                .read(start, end)
+                .map_err(|err| GvdbReaderError::Io(err, None))
+        }
+        .map_err(|err| err.with_range(start as usize, end as usize, alignment as usize))
+    }
+
+    /// The limits that are enforced when reading this file
+    pub fn limits(&self) -> &GvdbReaderLimits {
+        &self.limits
+    }
+
+    /// Change the limits that are enforced when reading this file
+    pub fn set_limits(&mut self, limits: GvdbReaderLimits) {
+        self.limits = limits;
+    }
+
+    /// Returns the root hash table of the file
+    pub fn hash_table(&self) -> GvdbReaderResult<LazyHashTable<R>> {
+        LazyHashTable::new(self, self.header.root(), Vec::new())
+    }
+
+    fn get_key(&self, item: &GvdbHashItem) -> GvdbReaderResult<String> {
+        self.limits.check_key_length(item.key_ptr().size())?;
+        Ok(String::from_utf8(self.read(&item.key_ptr(), 1)?)?)
+    }
+}
+
+impl<R> Debug for LazyGvdbFile<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyGvdbFile")
+            .field("header", &self.header)
+            .field("byteswapped", &self.byteswapped)
+            .finish()
+    }
+}
+
+/// A hash table inside a [`LazyGvdbFile`]
+///
+/// The hash table structure is kept in memory, keys and values are read when needed.
+pub struct LazyHashTable<'a, R> {
+    file: &'a LazyGvdbFile<R>,
+    table: GvdbHashTableData<'static>,
+    path: Vec<String>,
+}
+
+impl<'a, R: Read + Seek> LazyHashTable<'a, R> {
+    fn new(
+        file: &'a LazyGvdbFile<R>,
+        pointer: &GvdbPointer,
+        path: Vec<String>,
+    ) -> GvdbReaderResult<Self> {
+        let data = file.read(pointer, 4)?;
+        let table = GvdbHashTableData::for_bytes(Cow::Owned(data))?;
+        file.limits.check_items(table.n_hash_items())?;
+        Ok(Self { file, table, path })
+    }
+
+    /// The keys of the items that lead from the root hash table of the file to this hash table
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Gets a list of keys contained in the hash table
+    pub fn get_names(&self) -> GvdbReaderResult<Vec<String>> {
+        self.table
+            .get_names(|item| self.file.get_key(item))
+            .map_err(|err| err.with_item(&self.path, None))
+    }
+
+    /// Gets the item at key `key`
+    pub fn get_hash_item(&self, key: &str) -> GvdbReaderResult<GvdbHashItem> {
+        self.find_hash_item(key).map(|(_, item)| item)
+    }
+
+    fn find_hash_item(&self, key: &str) -> GvdbReaderResult<(usize, GvdbHashItem)> {
+        self.file
+            .limits
+            .check_key_length(key.len())
+            .and_then(|()| {
+                self.table
+                    .find_hash_item(key, |item| self.file.get_key(item))
+            })
+            .map_err(|err| err.with_item(&self.path, None))
+    }
+
+    /// Get the item at key `key` and try to interpret it as a [`zvariant::OwnedValue`]
+    pub fn get_value(&self, key: &str) -> GvdbReaderResult<zvariant::OwnedValue> {
+        let (index, item) = self.find_hash_item(key)?;
+        self.get_value_for_item(key, &item)
+            .map_err(|err| err.with_item(&self.path, Some(index)))
+    }
+
+    fn get_value_for_item(
+        &self,
+        key: &str,
+        item: &GvdbHashItem,
+    ) -> GvdbReaderResult<zvariant::OwnedValue> {
+        let typ = item.typ()?;
+        if typ != GvdbHashItemType::Value {
+            return Err(GvdbReaderError::DataError(format!(
+                "Unable to parse item for key '{}' as GVariant: Expected type 'v', got type {}",
+                key, typ
+            )));
+        }
+
+        let data = self.file.read(item.value_ptr(), 8)?;
+        let value = GvdbFile::parse_value(&data, self.file.byteswapped)?;
+        Ok(zvariant::OwnedValue::from(value))
+    }
+
+    /// Get the item at key `key` and try to convert it from [`zvariant::OwnedValue`] to T
+    pub fn get<T>(&self, key: &str) -> GvdbReaderResult<T>
+    where
+        T: TryFrom<zvariant::OwnedValue>,
+    {
+        T::try_from(self.get_value(key)?).map_err(|_| {
+            GvdbReaderError::DataError("Can't convert Value to specified type".to_string())
+        })
+    }
+
+    /// Get the item at key `key` and try to interpret it as a [`LazyHashTable`]
+    pub fn get_hash_table(&self, key: &str) -> GvdbReaderResult<LazyHashTable<'a, R>> {
+        let (index, item) = self.find_hash_item(key)?;
+        let mut path = self.path.clone();
+        path.push(key.to_string());
+
+        item.typ()
+            .and_then(|typ| {
+                if typ != GvdbHashItemType::HashTable {
+                    return Err(GvdbReaderError::DataError(format!(
+                        "Unable to parse item for key '{}' as hash table: Expected type 'H', got type '{}'",
+                        key, typ
+                    )));
+                }
+
+                self.file.limits.check_depth(path.len())?;
+                Self::new(self.file, item.value_ptr(), path)
+            })
+            .map_err(|err| err.with_item(&self.path, Some(index)))
+    }
+}
+
+impl<'a, R> Debug for LazyHashTable<'a, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyHashTable")
+            .field("file", &self.file)
+            .field("table", &self.table)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BlockCache;
+    use crate::read::{GvdbReaderError, LazyGvdbFile};
+    use crate::test::*;
+    use crate::test::{assert_eq, assert_matches};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// A reader that counts how many bytes were read
+    struct CountingReader<R> {
+        inner: R,
+        count: Arc<AtomicUsize>,
+    }
+
+    impl<R: Read> Read for CountingReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = self.inner.read(buf)?;
+            self.count.fetch_add(len, Ordering::Relaxed);
+            Ok(len)
+        }
+    }
+
+    impl<R: Seek> Seek for CountingReader<R> {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_files() {
+        let file = LazyGvdbFile::open(&TEST_FILE_1).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_names().unwrap(), vec!["root_key"]);
+        let value = table.get_value("root_key").unwrap();
+        assert_eq!(value.value_signature(), "(uus)");
+
+        let file = LazyGvdbFile::open(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_names().unwrap(), vec!["string", "table"]);
+        assert_eq!(table.get::<String>("string").unwrap(), "test string");
+        let sub_table = table.get_hash_table("table").unwrap();
+        assert_eq!(sub_table.get::<u32>("int").unwrap(), 42);
+        println!("{:?}", sub_table);
+
+        let file = LazyGvdbFile::open(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_names().unwrap().len(), 12);
+        let value = table.get_value("/gvdb/rs/test/json/test.json").unwrap();
+        let json = value
+            .downcast_ref::<zvariant::Structure>()
+            .unwrap()
+            .fields();
+        assert_eq!(json[2].clone().downcast::<Vec<u8>>().unwrap().len(), 34);
+    }
+
+    #[test]
+    fn errors() {
+        let file = LazyGvdbFile::open(&TEST_FILE_2).unwrap();
+        let table = file.hash_table().unwrap();
+        assert_matches!(table.get_value("fail"), Err(GvdbReaderError::KeyError(_)));
+        let err = table.get_value("table").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataError(_));
+        assert_eq!(err.context().unwrap().item_index(), Some(1));
+        assert_matches!(
+            table
+                .get_hash_table("string")
+                .as_ref()
+                .map_err(GvdbReaderError::inner),
+            Err(GvdbReaderError::DataError(_))
+        );
+        assert_matches!(
+            table.get::<u32>("string"),
+            Err(GvdbReaderError::DataError(_))
+        );
+
+        let err = LazyGvdbFile::new(Cursor::new(vec![0u8; 10])).unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::DataOffset);
+        assert_eq!(err.context().unwrap().range(), Some(0..24));
+
+        let res = LazyGvdbFile::new(Cursor::new(vec![0u8; 100]));
+        assert_matches!(res, Err(GvdbReaderError::DataError(_)));
+
+        let res = LazyGvdbFile::open(&PathBuf::from("this_file_does_not_exist"));
+        assert_matches!(res, Err(GvdbReaderError::Io(..)));
+    }
+
+    #[test]
+    fn partial_reads() {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        let payload = vec![7u8; 64 * 1024];
+        for num in 0..32 {
+            table_builder
+                .insert_bytes(&format!("{}", num), &payload)
+                .unwrap();
+        }
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let total = data.len();
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let reader = CountingReader {
+            inner: Cursor::new(data),
+            count: count.clone(),
+        };
+
+        let file = LazyGvdbFile::new(reader).unwrap();
+        let table = file.hash_table().unwrap();
+        let value: Vec<u8> = table.get("17").unwrap();
+        assert_eq!(value, payload);
+
+        let read = count.load(Ordering::Relaxed);
+        assert!(read < total / 8, "Read {} of {} bytes", read, total);
+
+        // Keys are cached now
+        let _ = table.get_hash_item("17").unwrap();
+        assert_eq!(count.load(Ordering::Relaxed), read);
+    }
+
+    #[test]
+    fn block_cache() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut cache = BlockCache::new(Cursor::new(data), 16, 2).unwrap();
+        assert_eq!(cache.len, 256);
+
+        // Spanning two blocks
+        assert_eq!(cache.read(10, 20).unwrap(), (10..20).collect::<Vec<u8>>());
+        assert_eq!(cache.blocks.len(), 2);
+        assert_eq!(cache.blocks[0].0, 1);
+
+        // Evicts the least recently used block
+        assert_eq!(
+            cache.read(250, 256).unwrap(),
+            (250..=255).collect::<Vec<u8>>()
+        );
+        assert_eq!(cache.blocks.len(), 2);
+        assert_eq!(cache.blocks[0].0, 15);
+        assert_eq!(cache.blocks[1].0, 1);
+
+        // Larger than a block, not cached
+        assert_eq!(cache.read(0, 40).unwrap(), (0..40).collect::<Vec<u8>>());
+        assert_eq!(cache.blocks[0].0, 15);
+
+        assert_eq!(cache.read(5, 5).unwrap(), Vec::<u8>::new());
+
+        let mut cache = BlockCache::new(Cursor::new(vec![1u8; 10]), 4, 0).unwrap();
+        assert_eq!(cache.read(2, 3).unwrap(), vec![1]);
+        assert!(cache.blocks.is_empty());
+    }
+}
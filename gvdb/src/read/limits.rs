@@ -0,0 +1,224 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use alloc::format;
+
+/// Limits that protect against malicious or corrupted GVDB files
+///
+/// The limits are checked when reading a file. Exceeding any of them results in a
+/// [`GvdbReaderError::LimitExceeded`](crate::read::GvdbReaderError::LimitExceeded) error. The
+/// defaults are generous enough for all regular files, but can be lowered for files from
+/// untrusted sources.
+///
+/// ```
+/// use gvdb::read::{GvdbFile, GvdbReaderError, GvdbReaderLimits};
+///
+/// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+/// let mut file = GvdbFile::from_file(&path).unwrap();
+///
+/// let mut limits = GvdbReaderLimits::default();
+/// limits.max_depth = 0;
+/// file.set_limits(limits);
+///
+/// let table = file.hash_table().unwrap();
+/// let err = table.get_hash_table("table").unwrap_err();
+/// assert!(matches!(err.inner(), GvdbReaderError::LimitExceeded(_)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct GvdbReaderLimits {
+    /// The maximum number of hash tables that can be nested inside the root hash table
+    pub max_depth: usize,
+
+    /// The maximum number of items in a single hash table
+    pub max_items: usize,
+
+    /// The maximum length of the key of a single item in bytes
+    pub max_key_length: usize,
+
+    /// The maximum uncompressed size of a single GResource file in bytes. Compressed data is
+    /// never decompressed beyond the size that is stored alongside it.
+    pub max_resource_size: usize,
+
+    /// The maximum ratio between the uncompressed size of a compressed GResource file and the
+    /// size of its compressed data. zlib can't compress data by more than a factor of about
+    /// 1032, so a higher ratio means that the stored size is wrong.
+    pub max_compression_ratio: usize,
+}
+
+impl Default for GvdbReaderLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_items: 1 << 20,
+            max_key_length: 1 << 16,
+            max_resource_size: 1 << 28,
+            max_compression_ratio: 1032,
+        }
+    }
+}
+
+impl GvdbReaderLimits {
+    fn check(value: usize, max: usize, what: &str) -> GvdbReaderResult<()> {
+        if value > max {
+            Err(GvdbReaderError::LimitExceeded(format!(
+                "{} is {}, the maximum is {}",
+                what, value, max
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn check_depth(&self, depth: usize) -> GvdbReaderResult<()> {
+        Self::check(depth, self.max_depth, "Hash table nesting depth")
+    }
+
+    pub(crate) fn check_items(&self, n_items: usize) -> GvdbReaderResult<()> {
+        Self::check(n_items, self.max_items, "Number of hash table items")
+    }
+
+    pub(crate) fn check_key_length(&self, length: usize) -> GvdbReaderResult<()> {
+        Self::check(length, self.max_key_length, "Key length")
+    }
+
+    #[cfg(feature = "gresource")]
+    pub(crate) fn check_resource_size(&self, size: usize) -> GvdbReaderResult<()> {
+        Self::check(size, self.max_resource_size, "GResource file size")
+    }
+
+    #[cfg(feature = "gresource")]
+    pub(crate) fn check_compression_ratio(
+        &self,
+        size: usize,
+        compressed_size: usize,
+    ) -> GvdbReaderResult<()> {
+        Self::check(
+            size,
+            compressed_size.saturating_mul(self.max_compression_ratio),
+            "Decompressed GResource file size",
+        )
+    }
+}
+
+#[cfg(all(feature = "std", test))]
+mod test {
+    use super::*;
+    use crate::read::{GvdbFile, GvdbKey, LazyGvdbFile};
+    use crate::test::{new_simple_file, TEST_FILE_2, TEST_FILE_3};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use matches::assert_matches;
+    use std::borrow::Cow;
+    use std::io::Cursor;
+
+    fn nested_file() -> Vec<u8> {
+        let mut table3 = GvdbHashTableBuilder::new();
+        table3.insert_string("string", "test").unwrap();
+        let mut table2 = GvdbHashTableBuilder::new();
+        table2.insert_table("table3", table3).unwrap();
+        let mut table1 = GvdbHashTableBuilder::new();
+        table1.insert_table("table2", table2).unwrap();
+        let mut root = GvdbHashTableBuilder::new();
+        root.insert_table("table1", table1).unwrap();
+        GvdbFileWriter::new().write_to_vec_with_table(root).unwrap()
+    }
+
+    fn limits(f: impl FnOnce(&mut GvdbReaderLimits)) -> GvdbReaderLimits {
+        let mut limits = GvdbReaderLimits::default();
+        f(&mut limits);
+        limits
+    }
+
+    #[test]
+    fn depth() {
+        let mut file = GvdbFile::from_bytes(Cow::Owned(nested_file())).unwrap();
+        file.set_limits(limits(|l| l.max_depth = 2));
+        assert_eq!(file.limits().max_depth, 2);
+
+        let root = file.hash_table().unwrap();
+        let table1 = root.get_hash_table("table1").unwrap();
+        let table2 = table1.get_hash_table("table2").unwrap();
+        let err = table2.get_hash_table("table3").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::LimitExceeded(_));
+        assert_eq!(err.context().unwrap().table_path(), ["table1", "table2"]);
+
+        let err = file.stats().unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::LimitExceeded(_));
+        assert!(file.dump().contains("Hash table nesting depth is 3"));
+
+        let err = GvdbFileWriter::for_big_endian()
+            .write_to_vec_with_file(&file)
+            .unwrap_err();
+        assert!(err.to_string().contains("Hash table nesting depth is 3"));
+    }
+
+    #[test]
+    fn depth_lazy() {
+        let mut file = LazyGvdbFile::new(Cursor::new(nested_file())).unwrap();
+        file.set_limits(limits(|l| l.max_depth = 1));
+
+        let root = file.hash_table().unwrap();
+        let table1 = root.get_hash_table("table1").unwrap();
+        let err = table1.get_hash_table("table2").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::LimitExceeded(_));
+    }
+
+    #[test]
+    fn items() {
+        let mut file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        file.set_limits(limits(|l| l.max_items = 11));
+        let err = file.hash_table().unwrap_err();
+        assert_matches!(err, GvdbReaderError::LimitExceeded(_));
+
+        file.set_limits(limits(|l| l.max_items = 12));
+        assert_eq!(file.hash_table().unwrap().get_names().unwrap().len(), 12);
+
+        let mut file = LazyGvdbFile::open(&TEST_FILE_3).unwrap();
+        file.set_limits(limits(|l| l.max_items = 11));
+        let err = file.hash_table().unwrap_err();
+        assert_matches!(err, GvdbReaderError::LimitExceeded(_));
+    }
+
+    #[test]
+    fn key_length() {
+        let mut file = new_simple_file(false);
+        file.set_limits(limits(|l| l.max_key_length = 3));
+        let table = file.hash_table().unwrap();
+        assert_matches!(
+            table.get_names().as_ref().map_err(GvdbReaderError::inner),
+            Err(GvdbReaderError::LimitExceeded(_))
+        );
+
+        // Keys that are too long can't be looked up
+        let err = table.get_value("test").unwrap_err();
+        assert_matches!(err.inner(), GvdbReaderError::LimitExceeded(_));
+        assert_matches!(
+            table.get_item_info_for_key(&GvdbKey::new("test")),
+            Err(GvdbReaderError::LimitExceeded(_))
+        );
+        assert!(!table.contains_key("test"));
+
+        // Also when looking up many keys, even if the key has a hint from an earlier lookup
+        let mut file = new_simple_file(false);
+        let key = GvdbKey::new("test");
+        file.hash_table()
+            .unwrap()
+            .get_item_info_for_key(&key)
+            .unwrap();
+        file.set_limits(limits(|l| l.max_key_length = 3));
+        let table = file.hash_table().unwrap();
+        let infos = table.get_many(&[key, GvdbKey::new("tes")]);
+        assert_matches!(infos[0], Err(GvdbReaderError::LimitExceeded(_)));
+        assert_matches!(infos[1], Err(GvdbReaderError::KeyError(_)));
+
+        let mut file = LazyGvdbFile::open(&TEST_FILE_2).unwrap();
+        file.set_limits(limits(|l| l.max_key_length = 3));
+        let table = file.hash_table().unwrap();
+        assert_matches!(
+            table.get_names().as_ref().map_err(GvdbReaderError::inner),
+            Err(GvdbReaderError::LimitExceeded(_))
+        );
+        assert_matches!(
+            table.get_value("string"),
+            Err(GvdbReaderError::LimitExceeded(_))
+        );
+    }
+}
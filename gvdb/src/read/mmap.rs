@@ -0,0 +1,169 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use crate::read::file::{GvdbData, GvdbFile};
+use std::fs::File;
+use std::path::Path;
+
+/// A hint for the kernel about how a memory-mapped GVDB file will be accessed
+///
+/// Hints are passed to `madvise`. They are ignored on platforms that don't support them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GvdbMmapAdvice {
+    /// The whole file will be needed soon and should be read ahead (`MADV_WILLNEED`)
+    WillNeed,
+
+    /// The file will be accessed in random order, read-ahead is not useful (`MADV_RANDOM`)
+    Random,
+
+    /// The file will be accessed sequentially (`MADV_SEQUENTIAL`)
+    Sequential,
+
+    /// Exclude the mapping from core dumps (`MADV_DONTDUMP`). Only supported on Linux.
+    DontDump,
+}
+
+impl GvdbMmapAdvice {
+    #[cfg(unix)]
+    fn apply(self, mmap: &memmap2::Mmap) -> std::io::Result<()> {
+        let advice = match self {
+            GvdbMmapAdvice::WillNeed => memmap2::Advice::WillNeed,
+            GvdbMmapAdvice::Random => memmap2::Advice::Random,
+            GvdbMmapAdvice::Sequential => memmap2::Advice::Sequential,
+            #[cfg(target_os = "linux")]
+            GvdbMmapAdvice::DontDump => memmap2::Advice::DontDump,
+            #[cfg(not(target_os = "linux"))]
+            GvdbMmapAdvice::DontDump => return Ok(()),
+        };
+
+        mmap.advise(advice)
+    }
+
+    #[cfg(not(unix))]
+    fn apply(self, _mmap: &memmap2::Mmap) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Options for memory-mapping a GVDB file
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::read::{GvdbMmapAdvice, GvdbMmapOptions};
+///
+/// let path = PathBuf::from("test-data/test3.gresource");
+/// let file = unsafe {
+///     GvdbMmapOptions::new()
+///         .populate(true)
+///         .advise(GvdbMmapAdvice::Random)
+///         .map(&path)
+///         .unwrap()
+/// };
+///
+/// let table = file.hash_table().unwrap();
+/// table.prefetch("/gvdb/rs/test/json/test.json").unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GvdbMmapOptions {
+    populate: bool,
+    private: bool,
+    advice: Vec<GvdbMmapAdvice>,
+}
+
+impl GvdbMmapOptions {
+    /// Create options for a shared, read-only mapping without any hints
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the whole file into memory when mapping it (`MAP_POPULATE`). This avoids page faults
+    /// on first access, at the cost of a slower [`map()`](Self::map). Only supported on Linux.
+    pub fn populate(&mut self, populate: bool) -> &mut Self {
+        self.populate = populate;
+        self
+    }
+
+    /// Create a private copy-on-write mapping (`MAP_PRIVATE`) instead of a shared one
+    pub fn private(&mut self, private: bool) -> &mut Self {
+        self.private = private;
+        self
+    }
+
+    /// Pass `advice` to the kernel after mapping the file. Can be called multiple times to
+    /// pass several hints.
+    pub fn advise(&mut self, advice: GvdbMmapAdvice) -> &mut Self {
+        if !self.advice.contains(&advice) {
+            self.advice.push(advice);
+        }
+        self
+    }
+
+    /// Open a file and `mmap` it into memory with these options
+    ///
+    /// # Safety
+    ///
+    /// See [`GvdbFile::from_file_mmap`]. A private mapping doesn't protect against the file being
+    /// modified on disk either, as pages that were not read yet still reflect the file contents.
+    pub unsafe fn map(&self, filename: &Path) -> GvdbReaderResult<GvdbFile<'static>> {
+        let file =
+            File::open(filename).map_err(GvdbReaderError::from_io_with_filename(filename))?;
+
+        let mut options = memmap2::MmapOptions::new();
+        if self.populate {
+            options.populate();
+        }
+
+        let mmap = if self.private {
+            options.map_copy_read_only(&file)
+        } else {
+            options.map(&file)
+        }
+        .map_err(GvdbReaderError::from_io_with_filename(filename))?;
+
+        for advice in &self.advice {
+            advice
+                .apply(&mmap)
+                .map_err(GvdbReaderError::from_io_with_filename(filename))?;
+        }
+
+        GvdbFile::from_data(GvdbData::Mmap(mmap))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{GvdbMmapAdvice, GvdbMmapOptions, GvdbReaderError};
+    use crate::test::*;
+    use crate::test::{assert_eq, assert_matches};
+    use std::path::PathBuf;
+
+    #[test]
+    fn options() {
+        let mut options = GvdbMmapOptions::new();
+        options
+            .populate(true)
+            .private(true)
+            .advise(GvdbMmapAdvice::WillNeed)
+            .advise(GvdbMmapAdvice::Random)
+            .advise(GvdbMmapAdvice::DontDump)
+            .advise(GvdbMmapAdvice::Random);
+        assert_eq!(options.advice.len(), 3);
+        println!("{:?}", options.clone());
+
+        let file = unsafe { options.map(&TEST_FILE_3).unwrap() };
+        assert_is_file_3(&file);
+
+        let file = unsafe {
+            GvdbMmapOptions::new()
+                .advise(GvdbMmapAdvice::Sequential)
+                .map(&TEST_FILE_2)
+                .unwrap()
+        };
+        assert_is_file_2(&file);
+    }
+
+    #[test]
+    fn errors() {
+        let res = unsafe { GvdbMmapOptions::new().map(&PathBuf::from("this_file_does_not_exist")) };
+        assert_matches!(res, Err(GvdbReaderError::Io(_, Some(_))));
+    }
+}
@@ -0,0 +1,428 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use crate::read::hash::GvdbHashTable;
+use crate::read::hash_item::{GvdbHashItem, GvdbHashItemType};
+use std::fmt::{Debug, Formatter};
+
+/// An item found with [`GvdbHashTable::lookup_path`]
+pub enum GvdbPathItem<'a> {
+    /// A value item
+    Value(zvariant::Value<'a>),
+
+    /// A hash table item that was inserted with
+    /// [`insert_table`](crate::write::GvdbHashTableBuilder::insert_table)
+    HashTable(GvdbHashTable<'a>),
+
+    /// A container that was created implicitly for a path separator. Contains the keys of the
+    /// direct children relative to the container. The keys of child containers end with the
+    /// path separator.
+    Container(Vec<String>),
+}
+
+impl<'a> Debug for GvdbPathItem<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GvdbPathItem::Value(value) => f.debug_tuple("Value").field(value).finish(),
+            GvdbPathItem::HashTable(table) => f
+                .debug_tuple("HashTable")
+                .field(&table.get_header())
+                .finish(),
+            GvdbPathItem::Container(children) => {
+                f.debug_tuple("Container").field(children).finish()
+            }
+        }
+    }
+}
+
+/// The current position while walking along a path. Items are containers, or values at the
+/// end of the path.
+enum Position<'a> {
+    Table(GvdbHashTable<'a>),
+    Item(GvdbHashTable<'a>, GvdbHashItem),
+}
+
+impl<'a> GvdbHashTable<'a> {
+    /// The indices of the items inside of the container `item`
+    pub(crate) fn container_indices(&self, item: &GvdbHashItem) -> GvdbReaderResult<Vec<usize>> {
+        let data = self.root.dereference(item.value_ptr(), 4)?;
+        Ok(data
+            .chunks_exact(4)
+            .map(|index| u32::from_le_bytes([index[0], index[1], index[2], index[3]]) as usize)
+            .collect())
+    }
+
+    /// The items inside of the container `item`
+    fn container_children(&self, item: &GvdbHashItem) -> GvdbReaderResult<Vec<GvdbHashItem>> {
+        self.container_indices(item)?
+            .into_iter()
+            .map(|index| self.table.get_hash_item_for_index(index))
+            .collect()
+    }
+
+    /// Find the item for `segment` in `candidates`. An item with the exact key wins over a
+    /// container whose key is `segment` followed by `separator`.
+    fn find_segment(
+        &self,
+        candidates: impl Iterator<Item = GvdbHashItem>,
+        segment: &str,
+        separator: &str,
+    ) -> GvdbReaderResult<Option<GvdbHashItem>> {
+        let mut container = None;
+        for item in candidates {
+            let key = self.root.get_key(&item)?;
+            if key == segment {
+                return Ok(Some(item));
+            } else if container.is_none()
+                && key.strip_prefix(segment) == Some(separator)
+                && item.typ()? == GvdbHashItemType::Container
+            {
+                container = Some(item);
+            }
+        }
+
+        Ok(container)
+    }
+
+    /// Find the item for `segment` at the top level of this hash table
+    fn find_root_segment(
+        &self,
+        segment: &str,
+        separator: &str,
+    ) -> GvdbReaderResult<Option<GvdbHashItem>> {
+        match self.get_hash_item(segment) {
+            Ok(item) => return Ok(Some(item)),
+            Err(err) if matches!(err.inner(), GvdbReaderError::KeyError(_)) => (),
+            Err(err) => return Err(err),
+        }
+
+        // The keys of top level items are complete, so containers can be found directly
+        match self.get_hash_item(&format!("{}{}", segment, separator)) {
+            Ok(item) if item.typ()? == GvdbHashItemType::Container => Ok(Some(item)),
+            Ok(_) => Ok(None),
+            Err(err) if matches!(err.inner(), GvdbReaderError::KeyError(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn lookup_segment(
+        position: Position<'a>,
+        segment: &str,
+        separator: &str,
+        last: bool,
+    ) -> GvdbReaderResult<Position<'a>> {
+        let (table, item) = match position {
+            Position::Table(table) => {
+                let item = table.find_root_segment(segment, separator)?;
+                (table, item)
+            }
+            Position::Item(table, container) => {
+                let children = table.container_children(&container)?;
+                let item = table.find_segment(children.into_iter(), segment, separator)?;
+                (table, item)
+            }
+        };
+
+        let item = item.ok_or_else(|| {
+            GvdbReaderError::KeyError(segment.to_string()).with_item(table.path(), None)
+        })?;
+        match item.typ()? {
+            GvdbHashItemType::HashTable => {
+                let path = table.child_path(&table.get_full_key(&item)?)?;
+                let sub_table = table.root.get_hash_table_for_item(&item)?.with_path(path);
+                Ok(Position::Table(sub_table))
+            }
+            GvdbHashItemType::Container => Ok(Position::Item(table, item)),
+            GvdbHashItemType::Value if last => Ok(Position::Item(table, item)),
+            GvdbHashItemType::Value => Err(GvdbReaderError::DataError(format!(
+                "Item '{}' is a value and can't contain other items",
+                segment
+            ))),
+        }
+    }
+
+    /// Look up an item by following `path` through nested hash tables and through the containers
+    /// that are created for the default path separator `/`. Each segment of the path is the key
+    /// of an item relative to the previous segment, without any path separator. Use
+    /// [`lookup_path_with_separator`](Self::lookup_path_with_separator) for hash tables that
+    /// were created with a different path separator.
+    ///
+    /// Returns the value, hash table or container at the end of the path. An empty path returns
+    /// this hash table. If a segment can't be found, the error is a [`GvdbReaderError::Path`]
+    /// with the index of the failing segment.
+    ///
+    /// ```
+    /// use gvdb::read::{GvdbFile, GvdbPathItem};
+    /// use gvdb::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    /// # use std::borrow::Cow;
+    ///
+    /// let mut app_table = GvdbHashTableBuilder::new();
+    /// app_table.insert("window/width", 800u32).unwrap();
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.insert_table("org.gnome.App", app_table).unwrap();
+    /// let data = GvdbFileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    ///
+    /// let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// let item = table.lookup_path(["org.gnome.App", "window", "width"]).unwrap();
+    /// assert!(matches!(item, GvdbPathItem::Value(value) if value == 800u32.into()));
+    /// ```
+    pub fn lookup_path<S: AsRef<str>>(
+        &self,
+        path: impl IntoIterator<Item = S>,
+    ) -> GvdbReaderResult<GvdbPathItem<'a>> {
+        let path = path.into_iter().map(|s| s.as_ref().to_string()).collect();
+        self.lookup_segments(path, "/")
+    }
+
+    fn lookup_segments(
+        &self,
+        path: Vec<String>,
+        separator: &str,
+    ) -> GvdbReaderResult<GvdbPathItem<'a>> {
+        let mut position = Position::Table(self.clone());
+        for (index, segment) in path.iter().enumerate() {
+            let last = index + 1 == path.len();
+            position = Self::lookup_segment(position, segment, separator, last).map_err(|err| {
+                GvdbReaderError::Path {
+                    path: path.clone(),
+                    segment: index,
+                    error: Box::new(err),
+                }
+            })?;
+        }
+
+        let error_context = |err| GvdbReaderError::Path {
+            segment: path.len().saturating_sub(1),
+            path: path.clone(),
+            error: Box::new(err),
+        };
+
+        match position {
+            Position::Table(table) => Ok(GvdbPathItem::HashTable(table)),
+            Position::Item(table, item) => {
+                if item.typ().map_err(error_context)? == GvdbHashItemType::Value {
+                    let value = table
+                        .root
+                        .get_value_for_item(&item)
+                        .map_err(error_context)?;
+                    return Ok(GvdbPathItem::Value(value));
+                }
+
+                let children = table
+                    .container_children(&item)
+                    .and_then(|children| {
+                        children
+                            .iter()
+                            .map(|child| table.root.get_key(child))
+                            .collect()
+                    })
+                    .map_err(error_context)?;
+                Ok(GvdbPathItem::Container(children))
+            }
+        }
+    }
+
+    /// Like [`lookup_path`](Self::lookup_path), with the segments of `path` separated by
+    /// `separator`, which is also the path separator of the containers
+    ///
+    /// ```
+    /// # use gvdb::read::{GvdbFile, GvdbPathItem};
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let table = file.hash_table().unwrap();
+    ///
+    /// let item = table.lookup_path_with_separator("/gvdb/rs/test", "/").unwrap();
+    /// assert!(matches!(item, GvdbPathItem::Container(_)));
+    /// ```
+    pub fn lookup_path_with_separator(
+        &self,
+        path: &str,
+        separator: &str,
+    ) -> GvdbReaderResult<GvdbPathItem<'a>> {
+        let path = path.split(separator).map(|s| s.to_string()).collect();
+        self.lookup_segments(path, separator)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{GvdbFile, GvdbPathItem, GvdbReaderError};
+    use crate::test::*;
+    use crate::test::{assert_eq, assert_matches};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::borrow::Cow;
+
+    fn nested_file() -> GvdbFile<'static> {
+        let mut app_table = GvdbHashTableBuilder::new();
+        app_table.insert("window/width", 800u32).unwrap();
+        app_table.insert("window/height", 600u32).unwrap();
+        app_table.insert_string("name", "App").unwrap();
+
+        let mut gnome_table = GvdbHashTableBuilder::new();
+        gnome_table.insert_table("app", app_table).unwrap();
+        gnome_table.insert_string("desktop/theme", "dark").unwrap();
+
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder
+            .insert_table("org/gnome", gnome_table)
+            .unwrap();
+        table_builder.insert_string("org/kde", "kde").unwrap();
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        GvdbFile::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    #[test]
+    fn lookup() {
+        let file = nested_file();
+
+        let item = file
+            .lookup_path(["org", "gnome", "app", "window", "width"])
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == 800u32.into());
+
+        let item = file.lookup_path(["org", "gnome", "app", "name"]).unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == "App".into());
+
+        let item = file
+            .lookup_path(["org", "gnome", "desktop", "theme"])
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == "dark".into());
+
+        let item = file.lookup_path(["org", "kde"]).unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == "kde".into());
+
+        let item = file.lookup_path(["org", "gnome", "app"]).unwrap();
+        match item {
+            GvdbPathItem::HashTable(table) => {
+                assert_eq!(table.get::<String>("name").unwrap(), "App")
+            }
+            other => panic!("Unexpected item: {:?}", other),
+        }
+
+        let item = file.lookup_path(["org", "gnome", "app", "window"]).unwrap();
+        match item {
+            GvdbPathItem::Container(mut children) => {
+                children.sort();
+                assert_eq!(children, vec!["height", "width"]);
+            }
+            other => panic!("Unexpected item: {:?}", other),
+        }
+
+        let item = file.lookup_path(["org"]).unwrap();
+        assert_matches!(item, GvdbPathItem::Container(children) if children.len() == 2);
+
+        let item = file.lookup_path(Vec::<String>::new()).unwrap();
+        assert_matches!(item, GvdbPathItem::HashTable(_));
+
+        let item = file
+            .hash_table()
+            .unwrap()
+            .lookup_path_with_separator("org/gnome/app/window/height", "/")
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == 600u32.into());
+        println!("{:?}", file.lookup_path(["org", "gnome"]).unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        let file = nested_file();
+
+        let err = file
+            .lookup_path(["org", "gnome", "fail", "width"])
+            .unwrap_err();
+        match &err {
+            GvdbReaderError::Path {
+                path,
+                segment,
+                error,
+            } => {
+                assert_eq!(path, &vec!["org", "gnome", "fail", "width"]);
+                assert_eq!(*segment, 2);
+                assert_matches!(error.inner(), GvdbReaderError::KeyError(key) if key == "fail");
+                assert_eq!(error.context().unwrap().table_path(), ["org/gnome"]);
+            }
+            other => panic!("Unexpected error: {:?}", other),
+        }
+        assert!(format!("{}", err).contains("segment 2 ('fail')"));
+
+        let err = file.lookup_path(["org", "kde", "value"]).unwrap_err();
+        assert_matches!(err, GvdbReaderError::Path { segment: 1, error, .. } if matches!(error.inner(), GvdbReaderError::DataError(_)));
+
+        let err = file.lookup_path(["org", ""]).unwrap_err();
+        assert_matches!(err, GvdbReaderError::Path { segment: 1, .. });
+    }
+
+    #[test]
+    fn prefix() {
+        // Containers whose keys only start with the segment are not matched
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.insert("windows/width", 800u32).unwrap();
+        table_builder.insert("organization/name", "GNOME").unwrap();
+        table_builder
+            .insert("org/gnome/windows/height", 600u32)
+            .unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+
+        for path in [
+            &["window", "width"][..],
+            &["org", "name"],
+            &["org", "gnome", "window", "height"],
+        ] {
+            let err = file.lookup_path(path).unwrap_err();
+            assert_matches!(err, GvdbReaderError::Path { error, .. } if matches!(*error, GvdbReaderError::KeyError(_)));
+        }
+
+        let item = file
+            .lookup_path(["org", "gnome", "windows", "height"])
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == 600u32.into());
+        let item = file.lookup_path(["organization", "name"]).unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == "GNOME".into());
+    }
+
+    #[test]
+    fn separator() {
+        let mut table_builder = GvdbHashTableBuilder::with_path_separator(Some(":"));
+        table_builder.insert("window:width", 800u32).unwrap();
+        table_builder.insert("windows:height", 600u32).unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let item = table
+            .lookup_path_with_separator("window:width", ":")
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == 800u32.into());
+        let item = table.lookup_path_with_separator("window", ":").unwrap();
+        assert_matches!(item, GvdbPathItem::Container(children) if children == vec!["width"]);
+
+        // The containers of this table don't use the default separator
+        assert!(table.lookup_path(["window", "width"]).is_err());
+        assert!(table
+            .lookup_path_with_separator("window:height", ":")
+            .is_err());
+    }
+
+    #[test]
+    fn test_file_3() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let item = table
+            .lookup_path_with_separator("/gvdb/rs/test/json/test.json", "/")
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Value(_));
+
+        let item = table
+            .lookup_path(["", "gvdb", "rs", "test", "json"])
+            .unwrap();
+        assert_matches!(item, GvdbPathItem::Container(children) if children == vec!["test.json"]);
+    }
+}
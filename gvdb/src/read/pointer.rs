@@ -24,11 +24,11 @@ impl GvdbPointer {
     }
 
     pub fn size(&self) -> usize {
-        (self.end() - self.start()) as usize
+        self.end().saturating_sub(self.start()) as usize
     }
 }
 
-#[cfg(test)]
+#[cfg(all(feature = "std", test))]
 mod test {
     use crate::read::GvdbPointer;
 
@@ -0,0 +1,419 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use crate::read::hash::GvdbHashTable;
+use crate::read::hash_item::GvdbHashItemType;
+
+/// Whether an item is part of the result of a query, and whether items below it can be
+struct Visit {
+    matches: bool,
+    descend: bool,
+}
+
+/// A glob pattern over keys that are separated into segments by `/`
+struct Glob<'p> {
+    segments: Vec<&'p str>,
+}
+
+impl<'p> Glob<'p> {
+    fn new(pattern: &'p str) -> Self {
+        Self {
+            segments: pattern.split('/').collect(),
+        }
+    }
+
+    /// The segments before the first wildcard, followed by a separator. Only the items below
+    /// the container with this key can match the pattern.
+    fn literal_prefix(&self) -> String {
+        let literal = self
+            .segments
+            .iter()
+            .position(|segment| segment.contains('*'))
+            .unwrap_or(self.segments.len())
+            .min(self.segments.len() - 1);
+
+        if literal == 0 {
+            String::new()
+        } else {
+            self.segments[..literal].join("/") + "/"
+        }
+    }
+
+    /// Add the positions that can be reached by matching `**` against zero segments
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut index = 0;
+        while index < states.len() {
+            let state = states[index];
+            if self.segments.get(state) == Some(&"**") && !states.contains(&(state + 1)) {
+                states.push(state + 1);
+            }
+            index += 1;
+        }
+
+        states
+    }
+
+    /// The positions in the pattern that can be reached after matching all segments of `key`.
+    /// The separator at the end of container keys is ignored.
+    fn states(&self, key: &str) -> Vec<usize> {
+        let key = key.strip_suffix('/').unwrap_or(key);
+        let mut states = self.closure(vec![0]);
+        for segment in key.split('/') {
+            let mut next = Vec::new();
+            for &state in &states {
+                let next_state = match self.segments.get(state) {
+                    Some(&"**") => state,
+                    Some(pattern) if match_segment(pattern, segment) => state + 1,
+                    _ => continue,
+                };
+
+                if !next.contains(&next_state) {
+                    next.push(next_state);
+                }
+            }
+
+            states = self.closure(next);
+            if states.is_empty() {
+                break;
+            }
+        }
+
+        states
+    }
+
+    fn visit(&self, key: &str, container: bool) -> Visit {
+        let states = self.states(key);
+        Visit {
+            matches: states.contains(&self.segments.len()),
+            descend: container && states.iter().any(|&state| state < self.segments.len()),
+        }
+    }
+}
+
+/// Match a single segment against a pattern in which `*` matches any number of characters
+fn match_segment(pattern: &str, segment: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let segment = segment.as_bytes();
+    let (mut p, mut s) = (0, 0);
+    // The position of the last `*` in the pattern and the position in the segment it matched
+    let mut star = None;
+
+    while s < segment.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, s));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == segment[s] {
+            p += 1;
+            s += 1;
+        } else if let Some((star_p, star_s)) = star {
+            // Let the last `*` match one more byte
+            p = star_p + 1;
+            s = star_s + 1;
+            star = Some((star_p, star_s + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+impl<'a> GvdbHashTable<'a> {
+    /// Find the full keys of all items that `visit` accepts. The search starts at the deepest
+    /// container whose key is a prefix of `prefix` that ends with a separator, or at the top level
+    /// items if there is none.
+    fn find_keys(
+        &self,
+        prefix: &str,
+        visit: impl Fn(&str, bool) -> Visit,
+    ) -> GvdbReaderResult<Vec<String>> {
+        let n_items = self.table.n_hash_items();
+        let mut pending = Vec::new();
+        for (end, _) in prefix.match_indices('/').rev() {
+            let key = &prefix[..=end];
+            match self.find_hash_item(key) {
+                Ok((index, item)) if item.typ()? == GvdbHashItemType::Container => {
+                    pending.push((index, key.to_string()));
+                    break;
+                }
+                Ok(_) => (),
+                Err(err) if matches!(err.inner(), GvdbReaderError::KeyError(_)) => (),
+                Err(err) => return Err(err),
+            }
+        }
+
+        if pending.is_empty() {
+            for index in (0..n_items).rev() {
+                let item = self
+                    .table
+                    .get_hash_item_for_index(index)
+                    .map_err(self.error_context(Some(index)))?;
+                if item.parent() == u32::MAX {
+                    let key = self
+                        .root
+                        .get_key(&item)
+                        .map_err(self.error_context(Some(index)))?;
+                    pending.push((index, key));
+                }
+            }
+        }
+
+        let mut keys = Vec::new();
+        // Containers that list each other must not be visited again
+        let mut visited = vec![false; n_items];
+        while let Some((index, key)) = pending.pop() {
+            if std::mem::replace(&mut visited[index], true) {
+                continue;
+            }
+
+            let item = self
+                .table
+                .get_hash_item_for_index(index)
+                .map_err(self.error_context(Some(index)))?;
+            let container =
+                item.typ().map_err(self.error_context(Some(index)))? == GvdbHashItemType::Container;
+            let Visit { matches, descend } = visit(&key, container);
+
+            if descend {
+                let children = self
+                    .container_indices(&item)
+                    .map_err(self.error_context(Some(index)))?;
+                for &child in children.iter().rev() {
+                    let child_item = self
+                        .table
+                        .get_hash_item_for_index(child)
+                        .map_err(self.error_context(Some(child)))?;
+                    if child_item.parent() as usize != index {
+                        return Err(GvdbReaderError::DataError(format!(
+                            "Container '{}' contains an item with a different parent",
+                            key
+                        ))
+                        .with_item(self.path(), Some(index)));
+                    }
+
+                    let child_key = self
+                        .root
+                        .get_key(&child_item)
+                        .map_err(self.error_context(Some(child)))?;
+                    pending.push((child, key.clone() + &child_key));
+                }
+            }
+
+            if matches {
+                keys.push(key);
+            }
+        }
+
+        Ok(keys)
+    }
+
+    /// The full keys of all items in this hash table that start with `prefix`
+    ///
+    /// Only the containers that lead to `prefix` and the items below them are read, instead of
+    /// all keys in the hash table. Keys are returned depth first: The key of a container comes
+    /// before the keys of the items inside of it. This includes the keys of containers, which
+    /// end with the path separator.
+    ///
+    /// ```
+    /// # use gvdb::read::GvdbFile;
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let table = file.hash_table().unwrap();
+    ///
+    /// let keys = table.keys_with_prefix("/gvdb/rs/test/json/").unwrap();
+    /// assert_eq!(keys, ["/gvdb/rs/test/json/", "/gvdb/rs/test/json/test.json"]);
+    /// ```
+    pub fn keys_with_prefix(&self, prefix: &str) -> GvdbReaderResult<Vec<String>> {
+        self.find_keys(prefix, |key, container| {
+            let matches = key.starts_with(prefix);
+            Visit {
+                matches,
+                descend: container && (matches || prefix.starts_with(key)),
+            }
+        })
+    }
+
+    /// The full keys of all items in this hash table that match the glob `pattern`
+    ///
+    /// The pattern is split into segments at `/`, the default path separator. A segment `*`
+    /// matches exactly one segment of a key, and `*` inside of a segment matches any number of
+    /// characters except the separator. A segment `**` matches any number of segments, including
+    /// none. The separator at the end of container keys is ignored for matching, so patterns
+    /// match containers like any other item.
+    ///
+    /// Only the containers that can lead to a matching key are read. Keys are returned in the
+    /// same order as with [`keys_with_prefix`](Self::keys_with_prefix).
+    ///
+    /// ```
+    /// # use gvdb::read::GvdbFile;
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let table = file.hash_table().unwrap();
+    ///
+    /// let keys = table.query("/gvdb/rs/test/*/test.*").unwrap();
+    /// assert_eq!(keys, ["/gvdb/rs/test/json/test.json"]);
+    ///
+    /// let keys = table.query("/gvdb/**/*.svg").unwrap();
+    /// assert_eq!(keys.len(), 2);
+    /// ```
+    pub fn query(&self, pattern: &str) -> GvdbReaderResult<Vec<String>> {
+        let glob = Glob::new(pattern);
+        self.find_keys(&glob.literal_prefix(), |key, container| {
+            glob.visit(key, container)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::GvdbFile;
+    use crate::test::{assert_eq, TEST_FILE_3};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::borrow::Cow;
+
+    fn settings_file(path_separator: Option<&str>) -> GvdbFile<'static> {
+        let mut table = GvdbHashTableBuilder::with_path_separator(path_separator);
+        table.insert("/org/app/editor/enabled", true).unwrap();
+        table.insert("/org/app/editor/font", "Mono").unwrap();
+        table.insert("/org/app/terminal/enabled", false).unwrap();
+        table.insert("/org/app/enabled", true).unwrap();
+        table.insert("/org/other/enabled", true).unwrap();
+        table.insert("/org/app/plugins/git/enabled", true).unwrap();
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table)
+            .unwrap();
+        GvdbFile::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    fn sorted(mut keys: Vec<String>) -> Vec<String> {
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn match_segment() {
+        assert!(super::match_segment("abc", "abc"));
+        assert!(!super::match_segment("abc", "abd"));
+        assert!(super::match_segment("*", ""));
+        assert!(super::match_segment("*", "abc"));
+        assert!(super::match_segment("a*c", "abbbc"));
+        assert!(super::match_segment("*.svg", "send-symbolic.svg"));
+        assert!(!super::match_segment("*.svg", "test.css"));
+        assert!(super::match_segment("*a*a", "banana"));
+        assert!(!super::match_segment("*a*b", "banana"));
+        assert!(super::match_segment("a**", "a"));
+    }
+
+    #[test]
+    fn literal_prefix() {
+        assert_eq!(
+            Glob::new("/org/app/*/enabled").literal_prefix(),
+            "/org/app/"
+        );
+        assert_eq!(Glob::new("/org/app/enabled").literal_prefix(), "/org/app/");
+        assert_eq!(Glob::new("/org/ap*/enabled").literal_prefix(), "/org/");
+        assert_eq!(Glob::new("/**").literal_prefix(), "/");
+        assert_eq!(Glob::new("*/enabled").literal_prefix(), "");
+        assert_eq!(Glob::new("enabled").literal_prefix(), "");
+    }
+
+    #[test]
+    fn keys_with_prefix() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let table = file.hash_table().unwrap();
+
+        let keys = table.keys_with_prefix("/gvdb/rs/test/icons/").unwrap();
+        assert_eq!(
+            keys,
+            [
+                "/gvdb/rs/test/icons/",
+                "/gvdb/rs/test/icons/scalable/",
+                "/gvdb/rs/test/icons/scalable/actions/",
+                "/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg",
+            ]
+        );
+
+        let keys = table.keys_with_prefix("/gvdb/rs/test/t").unwrap();
+        assert_eq!(keys, ["/gvdb/rs/test/test.css"]);
+
+        let keys = table.keys_with_prefix("").unwrap();
+        assert_eq!(sorted(keys), sorted(table.get_names().unwrap()));
+
+        assert!(table.keys_with_prefix("/missing/").unwrap().is_empty());
+        assert!(table.keys_with_prefix("gvdb").unwrap().is_empty());
+    }
+
+    #[test]
+    fn query() {
+        let file = settings_file(Some("/"));
+        let table = file.hash_table().unwrap();
+
+        let keys = table.query("/org/app/*/enabled").unwrap();
+        assert_eq!(
+            sorted(keys),
+            ["/org/app/editor/enabled", "/org/app/terminal/enabled"]
+        );
+
+        let keys = table.query("/org/*/enabled").unwrap();
+        assert_eq!(sorted(keys), ["/org/app/enabled", "/org/other/enabled"]);
+
+        let keys = table.query("/org/app/*").unwrap();
+        assert_eq!(
+            sorted(keys),
+            [
+                "/org/app/editor/",
+                "/org/app/enabled",
+                "/org/app/plugins/",
+                "/org/app/terminal/"
+            ]
+        );
+
+        let keys = table.query("/org/app/editor/font").unwrap();
+        assert_eq!(keys, ["/org/app/editor/font"]);
+        assert!(table.query("/org/app/missing").unwrap().is_empty());
+        assert!(table.query("org/*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn query_any_depth() {
+        let file = settings_file(Some("/"));
+        let table = file.hash_table().unwrap();
+
+        let keys = table.query("/org/**/enabled").unwrap();
+        assert_eq!(
+            sorted(keys),
+            [
+                "/org/app/editor/enabled",
+                "/org/app/enabled",
+                "/org/app/plugins/git/enabled",
+                "/org/app/terminal/enabled",
+                "/org/other/enabled",
+            ]
+        );
+
+        let keys = table.query("**/git/*").unwrap();
+        assert_eq!(keys, ["/org/app/plugins/git/enabled"]);
+
+        let keys = table.query("**").unwrap();
+        assert_eq!(sorted(keys), sorted(table.get_names().unwrap()));
+    }
+
+    #[test]
+    fn without_containers() {
+        let file = settings_file(None);
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_names().unwrap().len(), 6);
+
+        let keys = table.query("/org/app/*/enabled").unwrap();
+        assert_eq!(
+            sorted(keys),
+            ["/org/app/editor/enabled", "/org/app/terminal/enabled"]
+        );
+
+        let keys = table.keys_with_prefix("/org/app/editor/").unwrap();
+        assert_eq!(
+            sorted(keys),
+            ["/org/app/editor/enabled", "/org/app/editor/font"]
+        );
+    }
+}
@@ -0,0 +1,558 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use crate::read::file::GvdbFile;
+use crate::read::hash::GvdbHashTable;
+use crate::read::hash_item::{GvdbHashItem, GvdbHashItemType};
+use crate::read::shared::{OwnedHashTable, SharedGvdbFile};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::fs::{File, Metadata};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+type ChangedCallback = Box<dyn Fn(&SharedGvdbFile, &[String]) + Send + Sync>;
+type Loader = fn(&Path, File) -> GvdbReaderResult<GvdbFile<'static>>;
+
+/// Identifies one version of the file on disk
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FileId {
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileId {
+    fn new(metadata: &Metadata) -> Self {
+        #[cfg(unix)]
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            #[cfg(unix)]
+            dev: metadata.dev(),
+            #[cfg(unix)]
+            ino: metadata.ino(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        }
+    }
+}
+
+fn read_file(path: &Path, mut file: File) -> GvdbReaderResult<GvdbFile<'static>> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(GvdbReaderError::from_io_with_filename(path))?;
+    GvdbFile::from_bytes(Cow::Owned(data))
+}
+
+#[cfg(feature = "mmap")]
+fn map_file(path: &Path, file: File) -> GvdbReaderResult<GvdbFile<'static>> {
+    use crate::read::file::GvdbData;
+
+    // Safety: Upheld by the caller of ReloadingGvdbFile::open_mmap
+    let mmap = unsafe { memmap2::Mmap::map(&file) }
+        .map_err(GvdbReaderError::from_io_with_filename(path))?;
+    GvdbFile::from_data(GvdbData::Mmap(mmap))
+}
+
+/// Whether the item at `key` differs between `old` and `new`
+fn item_changed(old: &GvdbHashTable, new: &GvdbHashTable, key: &str) -> GvdbReaderResult<bool> {
+    let found = |result: GvdbReaderResult<GvdbHashItem>| match result {
+        Ok(item) => Ok(Some(item)),
+        Err(err) if matches!(err.inner(), GvdbReaderError::KeyError(_)) => Ok(None),
+        Err(err) => Err(err),
+    };
+    let (old_item, new_item) = match (
+        found(old.get_hash_item(key))?,
+        found(new.get_hash_item(key))?,
+    ) {
+        (Some(old_item), Some(new_item)) => (old_item, new_item),
+        (None, None) => return Ok(false),
+        _ => return Ok(true),
+    };
+
+    let typ = old_item.typ()?;
+    if typ != new_item.typ()? {
+        return Ok(true);
+    }
+
+    match typ {
+        GvdbHashItemType::Value => Ok(old.root.byteswapped != new.root.byteswapped
+            || old.root.get_bytes_for_item(&old_item)?
+                != new.root.get_bytes_for_item(&new_item)?),
+        GvdbHashItemType::HashTable => {
+            let old_table = old
+                .root
+                .get_hash_table_for_item(&old_item)?
+                .with_path(old.child_path(key)?);
+            let new_table = new
+                .root
+                .get_hash_table_for_item(&new_item)?
+                .with_path(new.child_path(key)?);
+            Ok(!changed_keys(&old_table, &new_table)?.is_empty())
+        }
+        // The children of a container are items of the same table and are compared on their own
+        GvdbHashItemType::Container => Ok(false),
+    }
+}
+
+/// The keys of `old` and `new` that were added, removed or changed, in sorted order
+fn changed_keys(old: &GvdbHashTable, new: &GvdbHashTable) -> GvdbReaderResult<Vec<String>> {
+    let mut keys = BTreeSet::new();
+    keys.extend(old.get_names()?);
+    keys.extend(new.get_names()?);
+
+    let mut changed = Vec::new();
+    for key in keys {
+        if item_changed(old, new, &key)? {
+            changed.push(key);
+        }
+    }
+
+    Ok(changed)
+}
+
+struct Inner {
+    path: PathBuf,
+    loader: Loader,
+    current: RwLock<(SharedGvdbFile, FileId)>,
+    // Serializes reloads, so concurrent reloads don't report the same change twice
+    reload_lock: Mutex<()>,
+    callback: Mutex<Option<ChangedCallback>>,
+}
+
+impl Inner {
+    fn load(path: &Path, loader: Loader) -> GvdbReaderResult<(SharedGvdbFile, FileId)> {
+        let file = File::open(path).map_err(GvdbReaderError::from_io_with_filename(path))?;
+        // Take the metadata from the opened file, so it matches the contents we load
+        let metadata = file
+            .metadata()
+            .map_err(GvdbReaderError::from_io_with_filename(path))?;
+        let gvdb_file = loader(path, file)?;
+        Ok((SharedGvdbFile::new(gvdb_file), FileId::new(&metadata)))
+    }
+
+    fn snapshot(&self) -> SharedGvdbFile {
+        self.current
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .0
+            .clone()
+    }
+
+    fn reload(&self) -> GvdbReaderResult<bool> {
+        let _guard = self
+            .reload_lock
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        let metadata = std::fs::metadata(&self.path)
+            .map_err(GvdbReaderError::from_io_with_filename(&self.path))?;
+        let current_id = self.current.read().unwrap_or_else(|err| err.into_inner()).1;
+        if FileId::new(&metadata) == current_id {
+            return Ok(false);
+        }
+
+        let (file, id) = Self::load(&self.path, self.loader)?;
+        let old = self.snapshot();
+        let changed = changed_keys(&old.file().hash_table()?, &file.file().hash_table()?)?;
+
+        *self.current.write().unwrap_or_else(|err| err.into_inner()) = (file.clone(), id);
+
+        if !changed.is_empty() {
+            let callback = self.callback.lock().unwrap_or_else(|err| err.into_inner());
+            if let Some(callback) = &*callback {
+                callback(&file, &changed);
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Watches the parent directory of the file with inotify and reloads when the file changes
+#[cfg(target_os = "linux")]
+struct Watcher {
+    watches: inotify::Watches,
+    wd: inotify::WatchDescriptor,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(target_os = "linux")]
+impl Watcher {
+    fn new(inner: &Arc<Inner>) -> GvdbReaderResult<Self> {
+        use inotify::{Inotify, WatchMask};
+
+        let file_name = inner
+            .path
+            .file_name()
+            .map(|name| name.to_owned())
+            .ok_or_else(|| {
+                GvdbReaderError::Io(
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "Path has no file name"),
+                    Some(inner.path.clone()),
+                )
+            })?;
+        let dir = match inner.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let inotify = Inotify::init().map_err(|err| GvdbReaderError::Io(err, None))?;
+        let mut watches = inotify.watches();
+        // Atomic replacement shows up as MOVED_TO, in-place writes as CLOSE_WRITE
+        let wd = watches
+            .add(
+                dir,
+                WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE,
+            )
+            .map_err(GvdbReaderError::from_io_with_filename(dir))?;
+
+        let weak = Arc::downgrade(inner);
+        let thread = std::thread::Builder::new()
+            .name("gvdb-reload".to_string())
+            .spawn(move || Self::run(inotify, weak, file_name))
+            .map_err(|err| GvdbReaderError::Io(err, None))?;
+
+        Ok(Self {
+            watches,
+            wd,
+            thread: Some(thread),
+        })
+    }
+
+    fn run(
+        mut inotify: inotify::Inotify,
+        inner: std::sync::Weak<Inner>,
+        file_name: std::ffi::OsString,
+    ) {
+        use inotify::EventMask;
+
+        let mut buffer = [0u8; 4096];
+        loop {
+            let events = match inotify.read_events_blocking(&mut buffer) {
+                Ok(events) => events,
+                Err(_) => return,
+            };
+
+            let mut changed = false;
+            for event in events {
+                if event.mask.contains(EventMask::IGNORED) {
+                    // The watch was removed, either on drop or because the directory is gone
+                    return;
+                }
+
+                changed |= event.mask.contains(EventMask::Q_OVERFLOW)
+                    || event.name == Some(file_name.as_os_str());
+            }
+
+            if changed {
+                match inner.upgrade() {
+                    // A failed reload keeps the previous file, the next event will try again
+                    Some(inner) => drop(inner.reload()),
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        // Removing the watch queues an IN_IGNORED event, which wakes up and ends the thread
+        let _ = self.watches.remove(self.wd.clone());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A GVDB file that is reloaded when it is replaced on disk
+///
+/// Writers like dconf replace the file atomically, by writing a new file and renaming it over the
+/// old one. `ReloadingGvdbFile` detects a new version of the file by comparing its inode,
+/// size and modification time, and swaps in the new contents. Readers get a consistent
+/// [`SharedGvdbFile`] snapshot from [`snapshot()`](Self::snapshot) that is never modified
+/// underneath them, even while a reload happens.
+///
+/// On Linux the directory of the file is watched with inotify and the file is reloaded
+/// automatically. On other platforms, or to check for changes synchronously, call
+/// [`reload()`](Self::reload).
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::read::ReloadingGvdbFile;
+///
+/// let file = ReloadingGvdbFile::open(&PathBuf::from("test-data/test2.gvdb")).unwrap();
+/// file.set_changed_callback(|_file, keys| println!("Changed keys: {:?}", keys));
+///
+/// let table = file.hash_table().unwrap();
+/// assert_eq!(table.get::<String>("string").unwrap(), "test string");
+/// ```
+pub struct ReloadingGvdbFile {
+    inner: Arc<Inner>,
+    #[cfg(target_os = "linux")]
+    _watcher: Watcher,
+}
+
+impl ReloadingGvdbFile {
+    fn with_loader(path: &Path, loader: Loader) -> GvdbReaderResult<Self> {
+        let current = Inner::load(path, loader)?;
+        let inner = Arc::new(Inner {
+            path: path.to_path_buf(),
+            loader,
+            current: RwLock::new(current),
+            reload_lock: Mutex::new(()),
+            callback: Mutex::new(None),
+        });
+
+        Ok(Self {
+            #[cfg(target_os = "linux")]
+            _watcher: Watcher::new(&inner)?,
+            inner,
+        })
+    }
+
+    /// Open a file and reload it whenever it changes. Every version of the file is read into
+    /// memory completely.
+    pub fn open(path: &Path) -> GvdbReaderResult<Self> {
+        Self::with_loader(path, read_file)
+    }
+
+    /// Open a file and reload it whenever it changes. Every version of the file is `mmap`ed into
+    /// memory.
+    ///
+    /// # Safety
+    ///
+    /// The file must only ever be replaced atomically by renaming a new file over it, and never
+    /// be modified or truncated in place. A replaced file stays mapped as long as snapshots of it
+    /// exist, so old snapshots remain valid.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn open_mmap(path: &Path) -> GvdbReaderResult<Self> {
+        Self::with_loader(path, map_file)
+    }
+
+    /// The path of the watched file
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// The most recently loaded version of the file
+    pub fn snapshot(&self) -> SharedGvdbFile {
+        self.inner.snapshot()
+    }
+
+    /// Returns the root hash table of the most recently loaded version of the file
+    pub fn hash_table(&self) -> GvdbReaderResult<OwnedHashTable> {
+        self.snapshot().hash_table()
+    }
+
+    /// Check whether the file on disk is different from the loaded file and reload it if it is.
+    /// Returns whether the file was reloaded.
+    ///
+    /// On failure the previously loaded file is kept.
+    pub fn reload(&self) -> GvdbReaderResult<bool> {
+        self.inner.reload()
+    }
+
+    /// Call `callback` with the new file and the sorted list of changed keys after each reload
+    /// that changed, added or removed keys of the root hash table. This replaces any previously
+    /// set callback.
+    ///
+    /// The callback is called from the watcher thread or from [`reload()`](Self::reload).
+    pub fn set_changed_callback(
+        &self,
+        callback: impl Fn(&SharedGvdbFile, &[String]) + Send + Sync + 'static,
+    ) {
+        *self
+            .inner
+            .callback
+            .lock()
+            .unwrap_or_else(|err| err.into_inner()) = Some(Box::new(callback));
+    }
+}
+
+impl Debug for ReloadingGvdbFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadingGvdbFile")
+            .field("path", &self.inner.path)
+            .field("file", &self.snapshot())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{GvdbReaderError, ReloadingGvdbFile};
+    use crate::test::{assert_eq, assert_matches};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "gvdb-reload-test-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Write a file with `entries` to a temporary file and rename it to `path`
+    fn replace_file(path: &Path, entries: &[(&str, &str)]) {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        for (key, value) in entries {
+            table_builder.insert_string(key, value).unwrap();
+        }
+
+        let mut table_builder_2 = GvdbHashTableBuilder::new();
+        table_builder_2.insert("int", 42u32).unwrap();
+        table_builder
+            .insert_table("table", table_builder_2)
+            .unwrap();
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, data).unwrap();
+        std::fs::rename(&tmp_path, path).unwrap();
+    }
+
+    #[test]
+    fn manual_reload() {
+        let dir = TempDir::new();
+        let path = dir.0.join("test.gvdb");
+        replace_file(&path, &[("a", "1"), ("b", "2")]);
+
+        let file = ReloadingGvdbFile::open(&path).unwrap();
+        assert_eq!(file.path(), path);
+        let (sender, receiver) = mpsc::channel();
+        file.set_changed_callback(move |_, keys| sender.send(keys.to_vec()).unwrap());
+
+        let old = file.hash_table().unwrap();
+        assert_eq!(file.reload().unwrap(), false);
+
+        replace_file(&path, &[("a", "1"), ("b", "3"), ("c", "4")]);
+        // The watcher thread may already have picked up the change
+        let _ = file.reload().unwrap();
+
+        let keys = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(keys, vec!["b", "c"]);
+        assert_eq!(file.hash_table().unwrap().get::<String>("b").unwrap(), "3");
+        println!("{:?}", file);
+
+        // Old snapshots are unaffected
+        assert_eq!(old.get::<String>("b").unwrap(), "2");
+        assert_matches!(old.get_value("c"), Err(GvdbReaderError::KeyError(_)));
+
+        // A failed reload keeps the file
+        std::fs::remove_file(&path).unwrap();
+        assert_matches!(file.reload(), Err(GvdbReaderError::Io(..)));
+        assert_eq!(file.hash_table().unwrap().get::<String>("a").unwrap(), "1");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn watch() {
+        let dir = TempDir::new();
+        let path = dir.0.join("test.gvdb");
+        replace_file(&path, &[("a", "1")]);
+
+        let file = ReloadingGvdbFile::open(&path).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        file.set_changed_callback(move |file, keys| {
+            let value = file.hash_table().unwrap().get::<String>("a").ok();
+            sender.send((keys.to_vec(), value)).unwrap()
+        });
+
+        // Unrelated files are ignored
+        std::fs::write(dir.0.join("other"), b"data").unwrap();
+
+        replace_file(&path, &[("a", "2")]);
+        let (keys, value) = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(keys, vec!["a"]);
+        assert_eq!(value.as_deref(), Some("2"));
+
+        replace_file(&path, &[]);
+        let (keys, value) = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(keys, vec!["a"]);
+        assert_eq!(value, None);
+
+        // Dropping stops the watcher thread
+        drop(file);
+    }
+
+    #[test]
+    fn nested_tables() {
+        let dir = TempDir::new();
+        let path = dir.0.join("test.gvdb");
+        replace_file(&path, &[("a", "1")]);
+        let file = ReloadingGvdbFile::open(&path).unwrap();
+        let (sender, receiver) = mpsc::channel();
+        file.set_changed_callback(move |_, keys| sender.send(keys.to_vec()).unwrap());
+
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.insert_string("a", "1").unwrap();
+        let mut table_builder_2 = GvdbHashTableBuilder::new();
+        table_builder_2.insert("int", 43u32).unwrap();
+        table_builder
+            .insert_table("table", table_builder_2)
+            .unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        std::fs::write(path.with_extension("tmp"), data).unwrap();
+        std::fs::rename(path.with_extension("tmp"), &path).unwrap();
+
+        // The watcher thread may already have picked up the change
+        let _ = file.reload().unwrap();
+
+        let keys = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(keys, vec!["table"]);
+
+        let table = file.hash_table().unwrap().get_hash_table("table").unwrap();
+        assert_eq!(table.get::<u32>("int").unwrap(), 43);
+    }
+
+    #[test]
+    fn errors() {
+        let res = ReloadingGvdbFile::open(&PathBuf::from("this_file_does_not_exist"));
+        assert_matches!(res, Err(GvdbReaderError::Io(..)));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap() {
+        let dir = TempDir::new();
+        let path = dir.0.join("test.gvdb");
+        replace_file(&path, &[("a", "1")]);
+
+        let file = unsafe { ReloadingGvdbFile::open_mmap(&path) }.unwrap();
+        let old = file.snapshot();
+        replace_file(&path, &[("a", "2")]);
+        let _ = file.reload().unwrap();
+
+        assert_eq!(file.hash_table().unwrap().get::<String>("a").unwrap(), "2");
+        assert_eq!(old.hash_table().unwrap().get::<String>("a").unwrap(), "1");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::read::error::GvdbReaderResult;
+use crate::read::file::GvdbFile;
+use crate::read::hash::{GvdbHashHeader, GvdbHashTable};
+use crate::read::hash_item::{GvdbHashItem, GvdbItemInfo};
+use crate::read::key::GvdbKey;
+use crate::read::path::GvdbPathItem;
+use crate::read::pointer::GvdbPointer;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A reference counted [`GvdbFile`] that can be cloned cheaply and shared between threads
+///
+/// Hash tables of a shared file are returned as [`OwnedHashTable`], which keeps the file alive
+/// on its own and can be stored next to other data or sent to a different thread.
+///
+/// ```
+/// use std::path::PathBuf;
+/// use gvdb::read::{GvdbFile, SharedGvdbFile};
+///
+/// let path = PathBuf::from("test-data/test2.gvdb");
+/// let file = SharedGvdbFile::new(GvdbFile::from_file(&path).unwrap());
+/// let table = file.hash_table().unwrap();
+///
+/// let handle = std::thread::spawn(move || {
+///     let sub_table = table.get_hash_table("table").unwrap();
+///     sub_table.get::<u32>("int").unwrap()
+/// });
+///
+/// assert_eq!(handle.join().unwrap(), 42);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedGvdbFile {
+    file: Arc<GvdbFile<'static>>,
+}
+
+impl SharedGvdbFile {
+    /// Move `file` into a new reference counted container
+    pub fn new(file: GvdbFile<'static>) -> Self {
+        Self {
+            file: Arc::new(file),
+        }
+    }
+
+    /// The underlying [`GvdbFile`]
+    pub fn file(&self) -> &GvdbFile<'static> {
+        &self.file
+    }
+
+    /// Returns the root hash table of the file
+    pub fn hash_table(&self) -> GvdbReaderResult<OwnedHashTable> {
+        let pointer = *self.file.get_header()?.root();
+        OwnedHashTable::new(self.clone(), pointer, Arc::new([]))
+    }
+}
+
+impl From<GvdbFile<'static>> for SharedGvdbFile {
+    fn from(file: GvdbFile<'static>) -> Self {
+        Self::new(file)
+    }
+}
+
+impl From<Arc<GvdbFile<'static>>> for SharedGvdbFile {
+    fn from(file: Arc<GvdbFile<'static>>) -> Self {
+        Self { file }
+    }
+}
+
+/// A hash table inside a [`SharedGvdbFile`]
+///
+/// Unlike [`GvdbHashTable`] this does not borrow from the file, but holds a reference to it.
+/// Cloning is cheap, as only the reference count of the file is increased.
+#[derive(Clone, Debug)]
+pub struct OwnedHashTable {
+    file: SharedGvdbFile,
+    /// Location of the hash table in the file, validated on construction
+    range: Range<usize>,
+    header: GvdbHashHeader,
+    path: Arc<[String]>,
+}
+
+impl OwnedHashTable {
+    fn new(
+        file: SharedGvdbFile,
+        pointer: GvdbPointer,
+        path: Arc<[String]>,
+    ) -> GvdbReaderResult<Self> {
+        // Make sure the table is valid before handing it out
+        let header = GvdbHashTable::for_bytes(file.file().dereference(&pointer, 4)?, file.file())
+            .map_err(|err| err.with_item(&path, None))?
+            .get_header();
+        Ok(Self {
+            file,
+            range: pointer.start() as usize..pointer.end() as usize,
+            header,
+            path,
+        })
+    }
+
+    /// The file this hash table belongs to
+    pub fn file(&self) -> &SharedGvdbFile {
+        &self.file
+    }
+
+    /// Borrow this hash table as a [`GvdbHashTable`]
+    pub fn table(&self) -> GvdbHashTable {
+        let file = self.file.file();
+        GvdbHashTable::for_validated_bytes(
+            &file.data.as_ref()[self.range.clone()],
+            self.header,
+            file,
+        )
+        .with_path(&self.path[..])
+    }
+
+    /// The keys of the items that lead from the root hash table of the file to this hash table
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// Gets a list of keys contained in the hash table
+    pub fn get_names(&self) -> GvdbReaderResult<Vec<String>> {
+        self.table().get_names()
+    }
+
+    /// Gets the item at key `key`
+    pub fn get_hash_item(&self, key: &str) -> GvdbReaderResult<GvdbHashItem> {
+        self.table().get_hash_item(key)
+    }
+
+    /// Whether the hash table contains an item with the key `key`
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.table().contains_key(key)
+    }
+
+    /// Gets the type, key and value pointer of the item at key `key` without reading the value
+    pub fn get_item_info(&self, key: &str) -> GvdbReaderResult<GvdbItemInfo> {
+        self.table().get_item_info(key)
+    }
+
+    /// Look up several keys at once. See [`GvdbHashTable::get_many`].
+    pub fn get_many(&self, keys: &[GvdbKey]) -> Vec<GvdbReaderResult<GvdbItemInfo>> {
+        self.table().get_many(keys)
+    }
+
+    /// Follow `path` through the nested hash tables and containers of this table. See
+    /// [`GvdbHashTable::lookup_path`].
+    pub fn lookup_path<S: AsRef<str>>(
+        &self,
+        path: impl IntoIterator<Item = S>,
+    ) -> GvdbReaderResult<GvdbPathItem> {
+        self.table().lookup_path(path)
+    }
+
+    /// Like [`lookup_path`](Self::lookup_path), with the segments of `path` separated by
+    /// `separator`. See [`GvdbHashTable::lookup_path_with_separator`].
+    pub fn lookup_path_with_separator(
+        &self,
+        path: &str,
+        separator: &str,
+    ) -> GvdbReaderResult<GvdbPathItem> {
+        self.table().lookup_path_with_separator(path, separator)
+    }
+
+    /// Get the item at key `key` and try to interpret it as a [`enum@zvariant::Value`]
+    pub fn get_value(&self, key: &str) -> GvdbReaderResult<zvariant::Value> {
+        let table = self.table();
+        let (index, item) = table.find_hash_item(key)?;
+        self.file
+            .file()
+            .get_value_for_item(&item)
+            .map_err(table.error_context(Some(index)))
+    }
+
+    /// Get the item at key `key` and try to convert it from [`enum@zvariant::Value`] to T
+    pub fn get<T>(&self, key: &str) -> GvdbReaderResult<T>
+    where
+        T: TryFrom<zvariant::OwnedValue>,
+    {
+        self.table().get(key)
+    }
+
+    #[cfg(feature = "glib")]
+    /// Get the item at key `key` and try to interpret it as a [`struct@glib::Variant`]
+    pub fn get_gvariant(&self, key: &str) -> GvdbReaderResult<glib::Variant> {
+        self.table().get_gvariant(key)
+    }
+
+    #[cfg(feature = "glib")]
+    /// Get the item at key `key` and convert the [`struct@glib::Variant`] inside of it to `T`
+    pub fn get_as_variant<T: glib::FromVariant>(&self, key: &str) -> GvdbReaderResult<T> {
+        self.table().get_as_variant(key)
+    }
+
+    /// Get the item at key `key` and try to interpret it as an [`OwnedHashTable`]
+    pub fn get_hash_table(&self, key: &str) -> GvdbReaderResult<OwnedHashTable> {
+        let table = self.table();
+        let (index, item) = table.find_hash_item(key)?;
+        // Checks the item type
+        self.file
+            .file()
+            .get_hash_table_for_item(&item)
+            .map_err(table.error_context(Some(index)))?;
+        let path = table
+            .child_path(key)
+            .map_err(table.error_context(Some(index)))?;
+        Self::new(self.file.clone(), *item.value_ptr(), path.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::{
+        GvdbFile, GvdbHashItemType, GvdbKey, GvdbPathItem, GvdbReaderError, OwnedHashTable,
+        SharedGvdbFile,
+    };
+    use crate::test::*;
+    use crate::test::{assert_eq, assert_matches};
+    use std::sync::Arc;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn send_sync() {
+        assert_send_sync::<SharedGvdbFile>();
+        assert_send_sync::<OwnedHashTable>();
+    }
+
+    #[test]
+    fn derives() {
+        let file = SharedGvdbFile::new(new_empty_file());
+        let table = file.hash_table().unwrap();
+        let table2 = table.clone();
+        println!("{:?}", table2);
+    }
+
+    #[test]
+    fn test_file_2() {
+        let file = SharedGvdbFile::from(GvdbFile::from_file(&TEST_FILE_2).unwrap());
+        let table = file.hash_table().unwrap();
+        assert_eq!(table.get_names().unwrap(), vec!["string", "table"]);
+        assert!(table.contains_key("table"));
+        assert!(!table.contains_key("int"));
+
+        let value: String = table.get("string").unwrap();
+        assert_eq!(value, "test string");
+        assert_eq!(
+            table.get_value("string").unwrap(),
+            zvariant::Value::from("test string")
+        );
+
+        let sub_table = table.get_hash_table("table").unwrap();
+        drop(table);
+        drop(file);
+
+        assert_eq!(sub_table.get_names().unwrap(), vec!["int"]);
+        assert_eq!(sub_table.get::<u32>("int").unwrap(), 42);
+        assert_is_file_2(sub_table.file().file());
+    }
+
+    #[test]
+    fn errors() {
+        let file = SharedGvdbFile::from(Arc::new(GvdbFile::from_file(&TEST_FILE_2).unwrap()));
+        let table = file.hash_table().unwrap();
+        assert_matches!(
+            table
+                .get_hash_table("string")
+                .as_ref()
+                .map_err(GvdbReaderError::inner),
+            Err(GvdbReaderError::DataError(_))
+        );
+        assert_matches!(
+            table.get_hash_table("fail"),
+            Err(GvdbReaderError::KeyError(_))
+        );
+        assert_matches!(
+            table
+                .get_value("table")
+                .as_ref()
+                .map_err(GvdbReaderError::inner),
+            Err(GvdbReaderError::DataError(_))
+        );
+    }
+
+    #[test]
+    fn lookup() {
+        let file = SharedGvdbFile::new(GvdbFile::from_file(&TEST_FILE_2).unwrap());
+        let table = file.hash_table().unwrap();
+
+        let info = table.get_item_info("table").unwrap();
+        assert_eq!(info.typ(), GvdbHashItemType::HashTable);
+        assert_matches!(
+            table.get_item_info("fail"),
+            Err(GvdbReaderError::KeyError(_))
+        );
+
+        let infos = table.get_many(&[GvdbKey::new("string"), GvdbKey::new("fail")]);
+        assert_eq!(infos[0].as_ref().unwrap().typ(), GvdbHashItemType::Value);
+        assert_matches!(infos[1], Err(GvdbReaderError::KeyError(_)));
+
+        let item = table.lookup_path(["table", "int"]).unwrap();
+        assert_matches!(item, GvdbPathItem::Value(value) if value == 42u32.into());
+        let item = table.lookup_path_with_separator("table", "/").unwrap();
+        assert_matches!(item, GvdbPathItem::HashTable(_));
+
+        let sub_table = table.get_hash_table("table").unwrap();
+        assert_eq!(sub_table.table().path(), ["table"]);
+        let err = sub_table.table().get_value("fail").unwrap_err();
+        assert_eq!(err.context().unwrap().table_path(), ["table"]);
+    }
+
+    #[test]
+    fn threads() {
+        let file = SharedGvdbFile::new(new_simple_file(false));
+        let table = file.hash_table().unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let table = table.clone();
+                std::thread::spawn(move || table.get::<String>("test").unwrap())
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "test");
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::read::error::GvdbReaderResult;
+use crate::read::file::GvdbFile;
+use crate::read::hash::GvdbHashTable;
+use crate::read::hash_item::GvdbHashItemType;
+use crate::read::header::GvdbHeader;
+use crate::read::pointer::GvdbPointer;
+use std::collections::HashSet;
+use std::mem::size_of;
+
+/// Statistics about a single hash table in a GVDB file
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct GvdbTableStats {
+    /// The keys of the hash table items that lead to this table. Empty for the root table.
+    pub path: Vec<String>,
+
+    /// The offset of the hash table structure in the file
+    pub offset: usize,
+
+    /// The size of the hash table structure in bytes (header, bloom filter, buckets and items)
+    pub size: usize,
+
+    /// The number of items in the hash table, including containers
+    pub n_items: usize,
+
+    /// The number of hash buckets
+    pub n_buckets: usize,
+
+    /// The number of buckets by the number of items they contain: `bucket_histogram[n]` is the
+    /// number of buckets with `n` items
+    pub bucket_histogram: Vec<usize>,
+
+    /// The number of items in the largest bucket
+    pub max_probe_length: usize,
+
+    /// The number of 32 bit bloom filter words
+    pub n_bloom_words: usize,
+
+    /// The fraction of bits that are set in the bloom filter. `0.0` if there is no bloom filter.
+    pub bloom_fill_ratio: f64,
+
+    /// The total size of the keys of all items in bytes
+    pub key_bytes: usize,
+
+    /// The total size of all values in bytes, not including nested hash tables
+    pub value_bytes: usize,
+}
+
+/// Statistics about a GVDB file, returned by [`GvdbFile::stats`]
+///
+/// ```
+/// # use gvdb::read::GvdbFile;
+/// let path = std::path::PathBuf::from("test-data/test3.gresource");
+/// let file = GvdbFile::from_file(&path).unwrap();
+/// let stats = file.stats().unwrap();
+///
+/// assert_eq!(stats.tables.len(), 1);
+/// assert_eq!(stats.tables[0].n_items, 12);
+/// assert_eq!(stats.unreachable_bytes, 0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct GvdbFileStats {
+    /// The size of the file in bytes
+    pub size: usize,
+
+    /// Statistics for every hash table in the file, starting with the root table
+    pub tables: Vec<GvdbTableStats>,
+
+    /// The total size of all keys in bytes
+    pub key_bytes: usize,
+
+    /// The total size of all values in bytes
+    pub value_bytes: usize,
+
+    /// The size of the file header, the hash table structures and the container child lists
+    pub structure_bytes: usize,
+
+    /// Bytes between data chunks that are required for alignment
+    pub padding_bytes: usize,
+
+    /// Bytes that are not referenced from anywhere and are not alignment padding
+    pub unreachable_bytes: usize,
+}
+
+/// A range of the file that is referenced from the header or a hash table
+struct Region {
+    start: usize,
+    end: usize,
+    alignment: usize,
+}
+
+#[derive(Default)]
+struct StatsCollector {
+    regions: Vec<Region>,
+    tables: Vec<GvdbTableStats>,
+    visited: HashSet<usize>,
+    structure_bytes: usize,
+}
+
+impl StatsCollector {
+    fn add_region(&mut self, pointer: &GvdbPointer, alignment: usize) {
+        self.regions.push(Region {
+            start: pointer.start() as usize,
+            end: pointer.end() as usize,
+            alignment,
+        });
+    }
+
+    fn visit_table(
+        &mut self,
+        file: &GvdbFile,
+        pointer: &GvdbPointer,
+        path: Vec<String>,
+    ) -> GvdbReaderResult<()> {
+        // Guard against hash tables that contain themselves
+        if !self.visited.insert(pointer.start() as usize) {
+            return Ok(());
+        }
+
+        file.limits.check_depth(path.len())?;
+        let table = GvdbHashTable::for_bytes(file.dereference(pointer, 4)?, file)?;
+        self.add_region(pointer, 4);
+        self.structure_bytes += pointer.size();
+
+        let header = table.get_header();
+        let bucket_sizes = table.table.bucket_sizes()?;
+        let max_probe_length = bucket_sizes.iter().copied().max().unwrap_or(0);
+        let mut bucket_histogram = vec![0; max_probe_length + 1];
+        for size in bucket_sizes {
+            bucket_histogram[size] += 1;
+        }
+
+        let bloom_words = table.table.bloom_words().unwrap_or_default();
+        let bloom_fill_ratio = if bloom_words.is_empty() {
+            0.0
+        } else {
+            let set_bits: u32 = bloom_words.iter().map(|word| word.count_ones()).sum();
+            set_bits as f64 / (bloom_words.len() * 32) as f64
+        };
+
+        let names = table.get_names()?;
+        let mut key_bytes = 0;
+        let mut value_bytes = 0;
+        let mut sub_tables = Vec::new();
+        for (index, name) in names.into_iter().enumerate() {
+            let item = table.table.get_hash_item_for_index(index)?;
+            let key_ptr = item.key_ptr();
+            self.add_region(&key_ptr, 1);
+            key_bytes += key_ptr.size();
+
+            let value_ptr = item.value_ptr();
+            match item.typ()? {
+                GvdbHashItemType::Value => {
+                    self.add_region(value_ptr, 8);
+                    value_bytes += value_ptr.size();
+                }
+                GvdbHashItemType::HashTable => {
+                    let mut sub_path = path.clone();
+                    sub_path.push(name);
+                    sub_tables.push((*value_ptr, sub_path));
+                }
+                GvdbHashItemType::Container => {
+                    self.add_region(value_ptr, 4);
+                    self.structure_bytes += value_ptr.size();
+                }
+            }
+        }
+
+        self.tables.push(GvdbTableStats {
+            path,
+            offset: pointer.start() as usize,
+            size: pointer.size(),
+            n_items: table.table.n_hash_items(),
+            n_buckets: header.n_buckets() as usize,
+            bucket_histogram,
+            max_probe_length,
+            n_bloom_words: header.n_bloom_words() as usize,
+            bloom_fill_ratio,
+            key_bytes,
+            value_bytes,
+        });
+
+        for (sub_pointer, sub_path) in sub_tables {
+            self.visit_table(file, &sub_pointer, sub_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sort the gaps between referenced regions into padding and unreachable bytes
+    fn gaps(&mut self, size: usize) -> (usize, usize) {
+        self.regions.sort_by_key(|region| region.start);
+
+        let mut padding_bytes = 0;
+        let mut unreachable_bytes = 0;
+        let mut covered_end = 0;
+        for region in &self.regions {
+            if region.start > covered_end {
+                let gap = region.start - covered_end;
+                if gap < region.alignment && region.start % region.alignment == 0 {
+                    padding_bytes += gap;
+                } else {
+                    unreachable_bytes += gap;
+                }
+            }
+
+            covered_end = covered_end.max(region.end);
+        }
+
+        unreachable_bytes += size.saturating_sub(covered_end);
+        (padding_bytes, unreachable_bytes)
+    }
+}
+
+impl<'a> GvdbFile<'a> {
+    /// Collect statistics about the layout of the file and all of its hash tables
+    ///
+    /// This can be used to choose bucket counts and bloom filter sizes, and to find data that is
+    /// not referenced from any hash table.
+    pub fn stats(&self) -> GvdbReaderResult<GvdbFileStats> {
+        let mut collector = StatsCollector::default();
+
+        let header_ptr = GvdbPointer::new(0, size_of::<GvdbHeader>());
+        collector.add_region(&header_ptr, 1);
+        collector.structure_bytes += header_ptr.size();
+
+        let header = self.get_header()?;
+        collector.visit_table(self, header.root(), Vec::new())?;
+
+        let size = self.data.as_ref().len();
+        let (padding_bytes, unreachable_bytes) = collector.gaps(size);
+
+        Ok(GvdbFileStats {
+            size,
+            key_bytes: collector.tables.iter().map(|table| table.key_bytes).sum(),
+            value_bytes: collector.tables.iter().map(|table| table.value_bytes).sum(),
+            tables: collector.tables,
+            structure_bytes: collector.structure_bytes,
+            padding_bytes,
+            unreachable_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::read::GvdbFile;
+    use crate::test::assert_eq;
+    use crate::test::*;
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_file_2() {
+        let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
+        let stats = file.stats().unwrap();
+        println!("{:?}", stats);
+
+        assert_eq!(stats.tables.len(), 2);
+        assert!(stats.tables[0].path.is_empty());
+        assert_eq!(stats.tables[0].n_items, 2);
+        assert_eq!(stats.tables[1].path, vec!["table"]);
+        assert_eq!(stats.tables[1].n_items, 1);
+        assert_eq!(
+            stats.key_bytes,
+            "string".len() + "table".len() + "int".len()
+        );
+        assert_eq!(stats.unreachable_bytes, 0);
+        assert_eq!(
+            stats.size,
+            stats.key_bytes + stats.value_bytes + stats.structure_bytes + stats.padding_bytes
+        );
+
+        for table in &stats.tables {
+            assert_eq!(
+                table.bucket_histogram.iter().sum::<usize>(),
+                table.n_buckets
+            );
+            assert_eq!(table.bucket_histogram.len(), table.max_probe_length + 1);
+        }
+    }
+
+    #[test]
+    fn test_file_3() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let stats = file.stats().unwrap();
+        assert_eq!(stats.tables.len(), 1);
+        assert_eq!(stats.unreachable_bytes, 0);
+        assert_eq!(
+            stats.size,
+            stats.key_bytes + stats.value_bytes + stats.structure_bytes + stats.padding_bytes
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let mut table_builder = GvdbHashTableBuilder::with_path_separator(None);
+        for num in 0..16 {
+            table_builder.insert(&format!("{}", num), num).unwrap();
+        }
+        table_builder.set_n_buckets(Some(4)).unwrap();
+        table_builder.set_bloom_filter(2, 5).unwrap();
+
+        let mut data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        // Junk at the end
+        data.extend_from_slice(&[1; 20]);
+
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let stats = file.stats().unwrap();
+        let table = &stats.tables[0];
+        assert_eq!(table.n_items, 16);
+        assert_eq!(table.n_buckets, 4);
+        assert_eq!(table.n_bloom_words, 2);
+        assert!(table.bloom_fill_ratio > 0.0 && table.bloom_fill_ratio <= 1.0);
+        assert!(table.max_probe_length >= 4);
+        assert_eq!(table.bucket_histogram.iter().sum::<usize>(), 4);
+        assert_eq!(table.value_bytes, stats.value_bytes);
+        assert_eq!(stats.unreachable_bytes, 20);
+    }
+
+    #[test]
+    fn empty() {
+        let stats = new_empty_file().stats().unwrap();
+        assert_eq!(stats.tables[0].n_items, 0);
+        assert_eq!(stats.tables[0].max_probe_length, 0);
+        assert_eq!(stats.tables[0].bloom_fill_ratio, 0.0);
+        assert_eq!(stats.unreachable_bytes, 0);
+    }
+}
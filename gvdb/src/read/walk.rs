@@ -0,0 +1,435 @@
+use crate::read::error::{GvdbReaderError, GvdbReaderResult};
+use crate::read::file::GvdbFile;
+use crate::read::hash::GvdbHashTable;
+use crate::read::hash_item::{GvdbHashItemType, GvdbItemInfo};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// An item that was visited by [`GvdbWalk`]
+#[derive(Clone, Debug)]
+pub struct GvdbWalkEntry<'a> {
+    path: Vec<String>,
+    depth: usize,
+    info: GvdbItemInfo<'a>,
+}
+
+impl<'a> GvdbWalkEntry<'a> {
+    /// The keys that lead from the root hash table to this item: The
+    /// [`path()`](GvdbHashTable::path) of the hash table of the item, followed by the full key of
+    /// the item in its hash table
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    /// The full key of the item in its hash table
+    pub fn key(&self) -> &str {
+        self.info.key()
+    }
+
+    /// The number of items above this item. Items at the top of the hash table the walk started
+    /// at have a depth of 0, items in containers and nested hash tables have the depth of the
+    /// container or hash table item plus one.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The type of the item
+    pub fn typ(&self) -> GvdbHashItemType {
+        self.info.typ()
+    }
+
+    /// Information about the item and its serialized data
+    pub fn info(&self) -> &GvdbItemInfo<'a> {
+        &self.info
+    }
+
+    /// Decode the value of the item. Fails for items that are not values.
+    #[cfg(feature = "std")]
+    pub fn value(&self) -> GvdbReaderResult<zvariant::Value<'a>> {
+        self.info.value()
+    }
+}
+
+/// A hash table that is being walked
+struct WalkTable<'a> {
+    table: GvdbHashTable<'a>,
+    names: Vec<String>,
+    // The items below each item, in file order
+    children: Vec<Vec<usize>>,
+    // The items that are left to visit with their depth, the next item last
+    pending: Vec<(usize, usize)>,
+}
+
+impl<'a> WalkTable<'a> {
+    fn new(table: GvdbHashTable<'a>, depth: usize) -> GvdbReaderResult<Self> {
+        let names = table.get_names()?;
+        let n_items = names.len();
+        let mut children = vec![Vec::new(); n_items];
+        let mut roots = Vec::new();
+        for index in 0..n_items {
+            let parent = table
+                .table
+                .get_hash_item_for_index(index)
+                .map_err(table.error_context(Some(index)))?
+                .parent();
+            // get_names() already checked that all parents are valid
+            match children.get_mut(parent as usize) {
+                Some(siblings) if parent != u32::MAX => siblings.push(index),
+                _ => roots.push(index),
+            }
+        }
+
+        let pending = roots
+            .into_iter()
+            .rev()
+            .map(|index| (index, depth))
+            .collect();
+        Ok(Self {
+            table,
+            names,
+            children,
+            pending,
+        })
+    }
+}
+
+/// What to visit after the last entry, unless its subtree is skipped. Usually only containers
+/// have items below them in the same hash table, but every item can be a parent.
+enum Descend<'a> {
+    Container(usize, usize),
+    HashTable(GvdbHashTable<'a>, usize),
+}
+
+/// An iterator over all items in a hash table and all of its nested hash tables, depth first
+///
+/// Items are visited in the order of the tree that their keys form: Every container or hash
+/// table item is directly followed by the items below it. Items on the same level are visited in
+/// the order they are stored in the file. Use [`skip_subtree()`](Self::skip_subtree) to not visit
+/// the items below the last entry.
+///
+/// Errors are returned as items of the iterator. If a hash table can't be read, its items are
+/// skipped, and the walk continues with the next item.
+///
+/// ```
+/// # use gvdb::read::{GvdbFile, GvdbHashItemType};
+/// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+/// let file = GvdbFile::from_file(&path).unwrap();
+///
+/// let mut paths = Vec::new();
+/// for entry in file.walk() {
+///     let entry = entry.unwrap();
+///     paths.push((entry.path().join(" > "), entry.depth(), entry.typ()));
+/// }
+///
+/// assert_eq!(
+///     paths,
+///     [
+///         ("string".to_string(), 0, GvdbHashItemType::Value),
+///         ("table".to_string(), 0, GvdbHashItemType::HashTable),
+///         ("table > int".to_string(), 1, GvdbHashItemType::Value),
+///     ]
+/// );
+/// ```
+///
+/// Skip the items in a subtree
+///
+/// ```
+/// # use gvdb::read::GvdbFile;
+/// let path = std::path::PathBuf::from("test-data/test3.gresource");
+/// let file = GvdbFile::from_file(&path).unwrap();
+///
+/// let mut walk = file.walk();
+/// let mut keys = Vec::new();
+/// while let Some(entry) = walk.next() {
+///     let entry = entry.unwrap();
+///     if entry.key() == "/gvdb/rs/test/icons/" {
+///         walk.skip_subtree();
+///     }
+///     keys.push(entry.key().to_string());
+/// }
+///
+/// assert!(keys.contains(&"/gvdb/rs/test/icons/".to_string()));
+/// assert!(!keys.iter().any(|key| key.starts_with("/gvdb/rs/test/icons/scalable")));
+/// ```
+pub struct GvdbWalk<'a> {
+    tables: Vec<WalkTable<'a>>,
+    descend: Option<Descend<'a>>,
+    error: Option<GvdbReaderError>,
+}
+
+impl<'a> GvdbWalk<'a> {
+    pub(crate) fn new(table: GvdbReaderResult<GvdbHashTable<'a>>) -> Self {
+        let mut this = Self {
+            tables: Vec::new(),
+            descend: None,
+            error: None,
+        };
+
+        match table {
+            Ok(table) => this.descend = Some(Descend::HashTable(table, 0)),
+            Err(err) => this.error = Some(err),
+        }
+
+        this
+    }
+
+    /// Don't visit the items below the entry that was returned last. This has no effect if the
+    /// entry is a value.
+    pub fn skip_subtree(&mut self) {
+        self.descend = None;
+    }
+
+    fn descend(&mut self) -> GvdbReaderResult<()> {
+        match self.descend.take() {
+            Some(Descend::Container(index, depth)) => {
+                if let Some(table) = self.tables.last_mut() {
+                    let children = core::mem::take(&mut table.children[index]);
+                    table
+                        .pending
+                        .extend(children.into_iter().rev().map(|child| (child, depth)));
+                }
+            }
+            Some(Descend::HashTable(table, depth)) => {
+                self.tables.push(WalkTable::new(table, depth)?);
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn next_entry(&mut self) -> Option<GvdbReaderResult<GvdbWalkEntry<'a>>> {
+        if let Err(err) = self.descend() {
+            return Some(Err(err));
+        }
+
+        let table = loop {
+            let table = self.tables.last_mut()?;
+            if table.pending.is_empty() {
+                self.tables.pop();
+            } else {
+                break table;
+            }
+        };
+
+        let (index, depth) = table.pending.pop()?;
+        let key = &table.names[index];
+        let mut path = table.table.path().to_vec();
+        path.push(key.clone());
+
+        let result = table
+            .table
+            .table
+            .get_hash_item_for_index(index)
+            .map_err(table.table.error_context(Some(index)))
+            .and_then(|item| {
+                let info = table.table.item_info(key, index, &item)?;
+                match info.typ() {
+                    GvdbHashItemType::Value | GvdbHashItemType::Container => {
+                        self.descend = Some(Descend::Container(index, depth + 1));
+                    }
+                    GvdbHashItemType::HashTable => {
+                        let child_path = table
+                            .table
+                            .child_path(key)
+                            .map_err(table.table.error_context(Some(index)))?;
+                        let sub_table = table
+                            .table
+                            .root
+                            .get_hash_table_for_item(&item)
+                            .map_err(table.table.error_context(Some(index)))?
+                            .with_path(child_path);
+                        self.descend = Some(Descend::HashTable(sub_table, depth + 1));
+                    }
+                }
+
+                Ok(GvdbWalkEntry { path, depth, info })
+            });
+
+        Some(result)
+    }
+}
+
+impl<'a> Iterator for GvdbWalk<'a> {
+    type Item = GvdbReaderResult<GvdbWalkEntry<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.error.take() {
+            return Some(Err(err));
+        }
+
+        self.next_entry()
+    }
+}
+
+impl<'a> GvdbHashTable<'a> {
+    /// Visit all items in this hash table and in all nested hash tables. See [`GvdbWalk`].
+    pub fn walk(&self) -> GvdbWalk<'a> {
+        GvdbWalk::new(Ok(self.clone()))
+    }
+}
+
+impl<'a> GvdbFile<'a> {
+    /// Visit all items in the file, starting at the root hash table. See [`GvdbWalk`].
+    pub fn walk(&self) -> GvdbWalk<'_> {
+        GvdbWalk::new(self.hash_table())
+    }
+}
+
+#[cfg(all(feature = "std", test))]
+mod test {
+    use crate::read::{GvdbFile, GvdbHashItemType, GvdbReaderError, GvdbReaderLimits};
+    use crate::test::{assert_eq, TEST_FILE_3};
+    use crate::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    use matches::assert_matches;
+    use std::borrow::Cow;
+
+    fn nested_file() -> GvdbFile<'static> {
+        let mut sub_table = GvdbHashTableBuilder::new();
+        sub_table.insert("a/b", 1u32).unwrap();
+        sub_table.insert("a/c", 2u32).unwrap();
+        let mut empty = GvdbHashTableBuilder::new();
+        empty
+            .insert_table("empty", GvdbHashTableBuilder::new())
+            .unwrap();
+
+        let mut root = GvdbHashTableBuilder::new();
+        root.insert_table("table", sub_table).unwrap();
+        root.insert_table("nested", empty).unwrap();
+        root.insert("x/y/z", "value").unwrap();
+
+        let data = GvdbFileWriter::new().write_to_vec_with_table(root).unwrap();
+        GvdbFile::from_bytes(Cow::Owned(data)).unwrap()
+    }
+
+    fn collect(file: &GvdbFile) -> Vec<(String, usize, GvdbHashItemType)> {
+        file.walk()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path().join(" > "), entry.depth(), entry.typ())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn walk() {
+        let file = nested_file();
+        let entries = collect(&file);
+        let position = |path: &str| entries.iter().position(|entry| entry.0 == path).unwrap();
+
+        // Every item comes after its parent
+        assert!(position("table") < position("table > a/"));
+        assert!(position("table > a/") < position("table > a/b"));
+        assert!(position("table > a/") < position("table > a/c"));
+        assert!(position("nested") < position("nested > empty"));
+        assert!(position("x/") < position("x/y/"));
+        assert!(position("x/y/") < position("x/y/z"));
+
+        let mut entries = entries.clone();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            entries,
+            [
+                ("nested".to_string(), 0, GvdbHashItemType::HashTable),
+                ("nested > empty".to_string(), 1, GvdbHashItemType::HashTable),
+                ("table".to_string(), 0, GvdbHashItemType::HashTable),
+                ("table > a/".to_string(), 1, GvdbHashItemType::Container),
+                ("table > a/b".to_string(), 2, GvdbHashItemType::Value),
+                ("table > a/c".to_string(), 2, GvdbHashItemType::Value),
+                ("x/".to_string(), 0, GvdbHashItemType::Container),
+                ("x/y/".to_string(), 1, GvdbHashItemType::Container),
+                ("x/y/z".to_string(), 2, GvdbHashItemType::Value),
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_values() {
+        let file = nested_file();
+        let root = file.hash_table().unwrap();
+        let table = root.get_hash_table("table").unwrap();
+
+        let mut values = Vec::new();
+        for entry in table.walk() {
+            let entry = entry.unwrap();
+            if entry.typ() == GvdbHashItemType::Value {
+                let value: u32 = entry.value().unwrap().try_into().unwrap();
+                values.push((entry.path().to_vec(), value));
+            } else {
+                assert_matches!(entry.value(), Err(GvdbReaderError::DataError(_)));
+            }
+        }
+
+        values.sort();
+        assert_eq!(
+            values,
+            [
+                (vec!["table".to_string(), "a/b".to_string()], 1),
+                (vec!["table".to_string(), "a/c".to_string()], 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn skip_subtree() {
+        let file = nested_file();
+        let mut walk = file.walk();
+        let mut paths = Vec::new();
+        while let Some(entry) = walk.next() {
+            let entry = entry.unwrap();
+            if entry.key() == "table" || entry.key() == "x/y/" {
+                walk.skip_subtree();
+            }
+            paths.push(entry.path().join(" > "));
+        }
+
+        paths.sort();
+        assert_eq!(paths, ["nested", "nested > empty", "table", "x/", "x/y/"]);
+
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let mut walk = file.walk();
+        let first = walk.next().unwrap().unwrap();
+        assert_eq!(first.key(), "/");
+        walk.skip_subtree();
+        assert!(walk.next().is_none());
+    }
+
+    #[test]
+    fn errors() {
+        let mut file = nested_file();
+        file.set_limits(GvdbReaderLimits {
+            max_depth: 1,
+            ..Default::default()
+        });
+
+        let mut errors = 0;
+        let mut paths = Vec::new();
+        for entry in file.walk() {
+            match entry {
+                Ok(entry) => paths.push(entry.path().join(" > ")),
+                Err(err) => {
+                    assert_matches!(err.inner(), GvdbReaderError::LimitExceeded(_));
+                    assert_eq!(err.context().unwrap().table_path(), ["nested"]);
+                    errors += 1;
+                }
+            }
+        }
+
+        // The walk continues after the error
+        assert_eq!(errors, 1);
+        assert!(paths.contains(&"x/y/z".to_string()));
+        assert!(paths.contains(&"table > a/b".to_string()));
+        assert!(!paths.contains(&"nested > empty".to_string()));
+
+        // Point the root hash table past the end of the file
+        let mut data = GvdbFileWriter::new()
+            .write_to_vec_with_table(GvdbHashTableBuilder::new())
+            .unwrap();
+        data[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let mut walk = file.walk();
+        assert_matches!(walk.next(), Some(Err(_)));
+        assert!(walk.next().is_none());
+    }
+}
@@ -139,11 +139,18 @@ pub fn byte_compare_gvdb_file(a: &GvdbFile, b: &GvdbFile) {
     byte_compare_gvdb_hash_table(&a_hash, &b_hash);
 }
 
-fn byte_compare_file(file: &GvdbFile, reference_path: &Path) {
+pub fn byte_compare_file(file: &GvdbFile, reference_path: &Path) {
     let mut reference_file = std::fs::File::open(reference_path).unwrap();
     let mut reference_data = Vec::new();
     reference_file.read_to_end(&mut reference_data).unwrap();
 
+    if reference_data != file.data.as_ref() {
+        if let Ok(reference) = GvdbFile::from_bytes(Cow::Borrowed(&reference_data)) {
+            println!("Reference file structure:\n{}", reference.dump());
+        }
+        println!("Generated file structure:\n{}", file.dump());
+    }
+
     assert_bytes_eq(
         &reference_data,
         file.data.as_ref(),
@@ -306,7 +313,7 @@ pub fn assert_is_file_3(file: &GvdbFile) {
     );
 }
 
-pub(crate) fn new_empty_file() -> GvdbFile {
+pub(crate) fn new_empty_file() -> GvdbFile<'static> {
     let writer = GvdbFileWriter::new();
     let table_builder = GvdbHashTableBuilder::new();
     let data = Vec::new();
@@ -316,7 +323,7 @@ pub(crate) fn new_empty_file() -> GvdbFile {
     GvdbFile::from_bytes(Cow::Owned(cursor.into_inner())).unwrap()
 }
 
-pub(crate) fn new_simple_file(big_endian: bool) -> GvdbFile {
+pub(crate) fn new_simple_file(big_endian: bool) -> GvdbFile<'static> {
     let writer = if big_endian {
         GvdbFileWriter::for_big_endian()
     } else {
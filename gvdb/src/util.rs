@@ -8,14 +8,68 @@ pub fn djb_hash(key: &str) -> u32 {
     hash_value
 }
 
+/// Run `f` on a new thread and wait for its result without blocking the async executor
+///
+/// A panic in `f` is resumed when the future is polled. There is no thread pool, every call
+/// spawns a new OS thread, so this is only used for work that takes much longer than that.
+#[cfg(feature = "async")]
+pub(crate) async fn spawn_blocking<T, F>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = futures_channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+        // The future was dropped if the receiver is gone, so nobody needs the result anymore
+        let _ = sender.send(result);
+    });
+
+    match receiver
+        .await
+        .expect("The blocking thread always sends its result")
+    {
+        Ok(value) => value,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
+
+/// A range of a [`struct@glib::Bytes`] that owns a reference to the complete data
+#[cfg(feature = "glib")]
+struct GlibBytesSlice {
+    bytes: glib::Bytes,
+    range: std::ops::Range<usize>,
+}
+
+#[cfg(feature = "glib")]
+impl AsRef<[u8]> for GlibBytesSlice {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[self.range.clone()]
+    }
+}
+
+/// Create a [`struct@glib::Bytes`] for `range` of `bytes` without copying the data
+#[cfg(feature = "glib")]
+pub(crate) fn slice_glib_bytes(bytes: &glib::Bytes, range: std::ops::Range<usize>) -> glib::Bytes {
+    if range.start == 0 && range.end == bytes.len() {
+        bytes.clone()
+    } else {
+        glib::Bytes::from_owned(GlibBytesSlice {
+            bytes: bytes.clone(),
+            range,
+        })
+    }
+}
+
 /// Align an arbitrary offset to a multiple of 2
 /// The result is undefined for alignments that are not a multiple of 2
+#[cfg(feature = "std")]
 pub fn align_offset(offset: usize, alignment: usize) -> usize {
     //(alignment - (offset % alignment)) % alignment
     (offset + alignment - 1) & !(alignment - 1)
 }
 
-#[cfg(test)]
+#[cfg(all(feature = "std", test))]
 mod test {
     use super::align_offset;
 
@@ -47,4 +101,19 @@ mod test {
         assert_eq!(align_offset(0, 1), 0);
         assert_eq!(align_offset(1, 1), 1);
     }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn spawn_blocking() {
+        let thread = std::thread::current().id();
+        let other =
+            futures_executor::block_on(super::spawn_blocking(|| std::thread::current().id()));
+        assert_ne!(thread, other);
+
+        let result = std::panic::catch_unwind(|| {
+            futures_executor::block_on(super::spawn_blocking(|| panic!("blocking panic")))
+        });
+        let panic = result.unwrap_err();
+        assert_eq!(panic.downcast_ref::<&str>(), Some(&"blocking panic"));
+    }
 }
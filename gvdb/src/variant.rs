@@ -0,0 +1,78 @@
+use byteorder::NativeEndian;
+use zvariant::{EncodingContext, OwnedValue, Value};
+
+/// Convert a [`enum@zvariant::Value`] to a [`struct@glib::Variant`] with the same type and contents
+///
+/// ```
+/// use glib::ToVariant;
+///
+/// let value = zvariant::Value::from("test");
+/// let variant = gvdb::variant::value_to_variant(&value).unwrap();
+/// assert_eq!(variant, "test".to_variant());
+/// ```
+pub fn value_to_variant(value: &Value) -> zvariant::Result<glib::Variant> {
+    // A value is serialized as a variant (`v`) that contains the value
+    let context = EncodingContext::<NativeEndian>::new_gvariant(0);
+    let data = zvariant::to_bytes(context, value)?;
+    glib::Variant::from_data_with_type(data, glib::VariantTy::VARIANT)
+        .as_variant()
+        .ok_or_else(|| {
+            zvariant::Error::Message(format!(
+                "Unable to convert value of type '{}' to GVariant",
+                value.value_signature()
+            ))
+        })
+}
+
+/// Convert a [`struct@glib::Variant`] to a [`enum@zvariant::Value`] with the same type and
+/// contents
+///
+/// ```
+/// use glib::ToVariant;
+///
+/// let value = gvdb::variant::variant_to_value(&42u32.to_variant()).unwrap();
+/// assert_eq!(u32::try_from(value).unwrap(), 42);
+/// ```
+pub fn variant_to_value(variant: &glib::Variant) -> zvariant::Result<OwnedValue> {
+    // GVariants are always serialized in the byte order of the host
+    let boxed = glib::Variant::from_variant(variant);
+    let context = EncodingContext::<NativeEndian>::new_gvariant(0);
+    let value: Value = zvariant::from_slice(boxed.data(), context)?;
+    Ok(value.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use glib::ToVariant;
+    use std::collections::HashMap;
+
+    #[test]
+    fn roundtrip() {
+        let mut dict = HashMap::new();
+        dict.insert("a".to_string(), (1u32, "one".to_string()));
+        dict.insert("b".to_string(), (2u32, "two".to_string()));
+
+        let variants = [
+            42u32.to_variant(),
+            "test".to_variant(),
+            vec![1u8, 2, 3].to_variant(),
+            dict.to_variant(),
+            "inner".to_variant().to_variant(),
+        ];
+
+        for variant in variants {
+            let value = variant_to_value(&variant).unwrap();
+            assert_eq!(value.value_signature().as_str(), variant.type_().as_str());
+            assert_eq!(value_to_variant(&value).unwrap(), variant);
+        }
+    }
+
+    #[test]
+    fn value_to_variant_struct() {
+        let value = Value::from((1u32, "test", vec![true, false]));
+        let variant = value_to_variant(&value).unwrap();
+        assert_eq!(variant.type_().as_str(), "(usab)");
+        assert_eq!(variant, (1u32, "test", vec![true, false]).to_variant());
+    }
+}
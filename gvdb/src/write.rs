@@ -2,6 +2,13 @@ mod error;
 mod file;
 mod hash;
 mod item;
+mod layout;
+mod stream;
+mod variant;
 
 pub use error::{GvdbBuilderResult, GvdbWriterError};
 pub use file::{GvdbFileWriter, GvdbHashTableBuilder};
+pub use item::GvdbEndianness;
+pub use layout::GvdbHashTableEstimate;
+#[cfg(feature = "gresource")]
+pub(crate) use stream::GvdbValueStream;
@@ -1,8 +1,10 @@
+use crate::read::GvdbReaderError;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use std::path::PathBuf;
 
 /// Error type for GvdbFileWriter
+#[non_exhaustive]
 pub enum GvdbWriterError {
     /// Generic I/O error. Path contains an optional filename if applicable
     Io(std::io::Error, Option<PathBuf>),
@@ -12,6 +14,18 @@ pub enum GvdbWriterError {
 
     /// An error occured when serializing variant data with zvariant
     ZVariant(zvariant::Error),
+
+    /// The requested hash table layout parameters can't be used
+    HashTableLayout(String),
+
+    /// An existing GVDB file that is being converted could not be read
+    Read(GvdbReaderError),
+
+    /// Serialized GVariant data does not match its type signature
+    InvalidValue(String),
+
+    /// The data does not fit into a GVDB file, as offsets in the file are 32 bit
+    FileSize(String),
 }
 
 impl Error for GvdbWriterError {}
@@ -28,6 +42,12 @@ impl From<zvariant::Error> for GvdbWriterError {
     }
 }
 
+impl From<GvdbReaderError> for GvdbWriterError {
+    fn from(err: GvdbReaderError) -> Self {
+        Self::Read(err)
+    }
+}
+
 impl Display for GvdbWriterError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -44,6 +64,18 @@ impl Display for GvdbWriterError {
             GvdbWriterError::ZVariant(err) => {
                 write!(f, "Error writing ZVariant data: {}", err)
             }
+            GvdbWriterError::HashTableLayout(context) => {
+                write!(f, "Invalid hash table layout: {}", context)
+            }
+            GvdbWriterError::Read(err) => {
+                write!(f, "Error reading GVDB file: {}", err)
+            }
+            GvdbWriterError::InvalidValue(context) => {
+                write!(f, "Invalid serialized value: {}", context)
+            }
+            GvdbWriterError::FileSize(context) => {
+                write!(f, "File too large: {}", context)
+            }
         }
     }
 }
@@ -75,5 +107,18 @@ mod test {
         );
         assert_matches!(err, GvdbWriterError::Io(..));
         assert!(format!("{}", err).contains("test_path"));
+
+        let err = GvdbWriterError::HashTableLayout("Test".to_string());
+        assert!(format!("{}", err).contains("hash table layout"));
+
+        let err = GvdbWriterError::from(crate::read::GvdbReaderError::InvalidData);
+        assert_matches!(err, GvdbWriterError::Read(_));
+        assert!(format!("{}", err).contains("reading GVDB file"));
+
+        let err = GvdbWriterError::InvalidValue("Test".to_string());
+        assert!(format!("{}", err).contains("Invalid serialized value"));
+
+        let err = GvdbWriterError::FileSize("Test".to_string());
+        assert!(format!("{}", err).contains("File too large"));
     }
 }
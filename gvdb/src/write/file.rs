@@ -2,13 +2,19 @@ use crate::read::GvdbHashHeader;
 use crate::read::GvdbHashItem;
 use crate::read::GvdbHeader;
 use crate::read::GvdbPointer;
+use crate::read::{GvdbFile, GvdbHashItemType, GvdbHashTable, GvdbReaderError};
 use crate::util::align_offset;
+use crate::util::djb_hash;
 use crate::write::error::{GvdbBuilderResult, GvdbWriterError};
-use crate::write::hash::SimpleHashTable;
-use crate::write::item::GvdbBuilderItemValue;
+use crate::write::hash::{SimpleHashTable, DEFAULT_BLOOM_SHIFT};
+use crate::write::item::{GvdbBuilderItemValue, GvdbEndianness};
+use crate::write::layout::GvdbHashTableEstimate;
+use crate::write::stream::GvdbValueStream;
+use crate::write::variant;
 use safe_transmute::transmute_one_to_bytes;
-use std::collections::{HashMap, VecDeque};
-use std::io::Write;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::{Read, Write};
 use std::mem::size_of;
 
 /// Create hash tables for use in GVDB files
@@ -30,6 +36,10 @@ use std::mem::size_of;
 pub struct GvdbHashTableBuilder<'a> {
     items: HashMap<String, GvdbBuilderItemValue<'a>>,
     path_separator: Option<String>,
+    n_buckets: Option<usize>,
+    load_factor: f32,
+    n_bloom_words: u32,
+    bloom_shift: u32,
 }
 
 impl<'a> GvdbHashTableBuilder<'a> {
@@ -53,9 +63,246 @@ impl<'a> GvdbHashTableBuilder<'a> {
         Self {
             items: Default::default(),
             path_separator: sep.map(|s| s.to_string()),
+            n_buckets: None,
+            load_factor: 1.0,
+            n_bloom_words: 0,
+            bloom_shift: DEFAULT_BLOOM_SHIFT,
         }
     }
 
+    /// Create a GvdbHashTableBuilder with all items of an existing hash table, to modify the
+    /// table and write it to a new file
+    ///
+    /// Values are copied as they are serialized in the file, without decoding them. They are
+    /// written unchanged if the file and the writer have the same byte order, and byteswapped
+    /// otherwise. Nested hash tables are copied recursively, and containers keep their children.
+    /// The bloom filter parameters of the table are kept as well. The builder uses the path
+    /// separator `/` for items that are inserted afterwards, use
+    /// [`from_table_with_path_separator`](Self::from_table_with_path_separator) for tables with
+    /// a different separator.
+    ///
+    /// ```
+    /// use gvdb::read::GvdbFile;
+    /// use gvdb::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    ///
+    /// let path = std::path::PathBuf::from("test-data/test3.gresource");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let mut table_builder = GvdbHashTableBuilder::from_table(&file.hash_table().unwrap()).unwrap();
+    /// assert!(table_builder.remove("/gvdb/rs/test/test.css"));
+    ///
+    /// let data = GvdbFileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    /// let file = GvdbFile::from_bytes(data.into()).unwrap();
+    /// let table = file.hash_table().unwrap();
+    /// assert!(!table.contains_key("/gvdb/rs/test/test.css"));
+    /// assert!(table.contains_key("/gvdb/rs/test/json/test.json"));
+    /// ```
+    pub fn from_table(table: &GvdbHashTable<'a>) -> GvdbBuilderResult<Self> {
+        Self::from_table_with_path_separator(table, Some("/"))
+    }
+
+    /// Like [`from_table`](Self::from_table), with a different path separator than `/` or none
+    /// at all. The separator is used for the nested hash tables as well.
+    ///
+    /// ```
+    /// use gvdb::read::GvdbFile;
+    /// use gvdb::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    ///
+    /// let mut table_builder = GvdbHashTableBuilder::with_path_separator(Some(":"));
+    /// table_builder.insert_string("a:b", "test").unwrap();
+    /// let data = GvdbFileWriter::new().write_to_vec_with_table(table_builder).unwrap();
+    /// let file = GvdbFile::from_bytes(data.into()).unwrap();
+    ///
+    /// let table = file.hash_table().unwrap();
+    /// let mut table_builder =
+    ///     GvdbHashTableBuilder::from_table_with_path_separator(&table, Some(":")).unwrap();
+    /// table_builder.insert_string("a:c", "test").unwrap();
+    /// ```
+    pub fn from_table_with_path_separator(
+        table: &GvdbHashTable<'a>,
+        sep: Option<&str>,
+    ) -> GvdbBuilderResult<Self> {
+        let header = table.get_header();
+        let mut builder = Self::with_path_separator(sep);
+        builder.set_bloom_filter(header.n_bloom_words(), header.bloom_shift())?;
+
+        let names = table.get_names()?;
+        for (index, name) in names.iter().enumerate() {
+            let item = table
+                .table
+                .get_hash_item_for_index(index)
+                .map_err(table.error_context(Some(index)))?;
+            let info = table.item_info(name, index, &item)?;
+
+            let value = match info.typ() {
+                GvdbHashItemType::Value => {
+                    let (Some(signature), Some(bytes)) = (info.signature(), info.value_bytes())
+                    else {
+                        return Err(GvdbReaderError::DataError(format!(
+                            "Unable to parse item for key '{}' as GVariant: Missing type signature",
+                            name
+                        ))
+                        .with_item(table.path(), Some(index))
+                        .into());
+                    };
+
+                    GvdbBuilderItemValue::Raw {
+                        signature: signature.to_string(),
+                        bytes: Cow::Borrowed(bytes),
+                        endianness: GvdbEndianness::from_byteswapped(info.is_byteswapped()),
+                    }
+                }
+                GvdbHashItemType::HashTable => {
+                    let path = table
+                        .child_path(name)
+                        .map_err(table.error_context(Some(index)))?;
+                    let sub_table = table
+                        .root
+                        .get_hash_table_for_item(&item)
+                        .map_err(table.error_context(Some(index)))?
+                        .with_path(path);
+                    GvdbBuilderItemValue::TableBuilder(Self::from_table_with_path_separator(
+                        &sub_table, sep,
+                    )?)
+                }
+                GvdbHashItemType::Container => {
+                    let children = table
+                        .container_indices(&item)
+                        .map_err(table.error_context(Some(index)))?
+                        .into_iter()
+                        .map(|child| {
+                            names.get(child).cloned().ok_or_else(|| {
+                                GvdbReaderError::DataError(format!(
+                                    "Child item index {} of container '{}' is out of range",
+                                    child, name
+                                ))
+                                .with_item(table.path(), Some(index))
+                            })
+                        })
+                        .collect::<Result<_, _>>()?;
+                    GvdbBuilderItemValue::Container(children)
+                }
+            };
+
+            // The items are inserted as they are, their containers are already part of the table
+            builder.items.insert(name.clone(), value);
+        }
+
+        Ok(builder)
+    }
+
+    /// Use a fixed number of hash buckets instead of calculating it from the load factor
+    ///
+    /// Passing `None` restores the default behaviour. A table that contains items always gets at
+    /// least one bucket. The number of buckets is stored as a 32 bit integer, larger values are
+    /// rejected.
+    ///
+    /// ```
+    /// # use gvdb::write::GvdbHashTableBuilder;
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.set_n_buckets(Some(64)).unwrap();
+    /// assert_eq!(table_builder.n_buckets(), 64);
+    /// ```
+    pub fn set_n_buckets(&mut self, n_buckets: Option<usize>) -> GvdbBuilderResult<()> {
+        if let Some(n_buckets) = n_buckets {
+            if u32::try_from(n_buckets).is_err() {
+                return Err(GvdbWriterError::HashTableLayout(format!(
+                    "Too many hash buckets: {}",
+                    n_buckets
+                )));
+            }
+        }
+
+        self.n_buckets = n_buckets;
+        Ok(())
+    }
+
+    /// Set the average number of items per hash bucket. The default is `1.0`, which is what GLib
+    /// uses.
+    ///
+    /// Higher values create smaller files with slower lookups. This has no effect when a fixed
+    /// number of buckets was set with [`set_n_buckets`](Self::set_n_buckets).
+    ///
+    /// ```
+    /// # use gvdb::write::GvdbHashTableBuilder;
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.set_load_factor(2.0).unwrap();
+    /// ```
+    pub fn set_load_factor(&mut self, load_factor: f32) -> GvdbBuilderResult<()> {
+        if !load_factor.is_finite() || load_factor <= 0.0 {
+            return Err(GvdbWriterError::HashTableLayout(format!(
+                "Load factor must be a positive number, got {}",
+                load_factor
+            )));
+        }
+
+        self.load_factor = load_factor;
+        Ok(())
+    }
+
+    /// Configure the bloom filter of the hash table. The default is to write no bloom filter,
+    /// which is what GLib does.
+    ///
+    /// The bloom filter lets lookups of keys that don't exist return early. `n_bloom_words` is the
+    /// number of 32 bit words in the filter, `bloom_shift` selects the second bit that is set for
+    /// each hash value and must be smaller than 32.
+    ///
+    /// ```
+    /// # use gvdb::write::GvdbHashTableBuilder;
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.set_bloom_filter(16, 5).unwrap();
+    /// ```
+    pub fn set_bloom_filter(
+        &mut self,
+        n_bloom_words: u32,
+        bloom_shift: u32,
+    ) -> GvdbBuilderResult<()> {
+        if n_bloom_words >= 1 << 27 {
+            return Err(GvdbWriterError::HashTableLayout(format!(
+                "Too many bloom words: {}",
+                n_bloom_words
+            )));
+        } else if bloom_shift >= 32 {
+            return Err(GvdbWriterError::HashTableLayout(format!(
+                "Bloom shift must be smaller than 32, got {}",
+                bloom_shift
+            )));
+        }
+
+        self.n_bloom_words = n_bloom_words;
+        self.bloom_shift = bloom_shift;
+        Ok(())
+    }
+
+    /// The number of hash buckets the table will be written with
+    pub fn n_buckets(&self) -> usize {
+        let n_items = self.items.len();
+        match self.n_buckets {
+            Some(n_buckets) if n_items > 0 => n_buckets.max(1),
+            Some(n_buckets) => n_buckets,
+            None => (n_items as f64 / self.load_factor as f64).ceil() as usize,
+        }
+    }
+
+    /// Estimate the lookup performance of the table with the current layout parameters
+    ///
+    /// This does not include nested tables, which have their own layout.
+    pub fn layout_estimate(&self) -> GvdbHashTableEstimate {
+        let hashes: Vec<u32> = self.items.keys().map(|key| djb_hash(key)).collect();
+        let n_buckets = self.n_buckets();
+        let size = size_of::<GvdbHashHeader>()
+            + self.n_bloom_words as usize * size_of::<u32>()
+            + n_buckets * size_of::<u32>()
+            + hashes.len() * size_of::<GvdbHashItem>();
+
+        GvdbHashTableEstimate::calculate(
+            &hashes,
+            n_buckets,
+            self.n_bloom_words,
+            self.bloom_shift,
+            size,
+        )
+    }
+
     fn insert_item_value(
         &mut self,
         key: &(impl ToString + ?Sized),
@@ -193,6 +440,106 @@ impl<'a> GvdbHashTableBuilder<'a> {
         self.insert_value(key, value)
     }
 
+    /// Insert a byte array for `key` that is read from `reader` only when the file is written
+    ///
+    /// Exactly `len` bytes are read from `reader` and written directly to the output. The data
+    /// is never held in memory as a whole, which allows creating files with very large values
+    /// when writing them with [`GvdbFileWriter::write_with_table`]. Writing fails with an I/O
+    /// error if the reader ends early.
+    ///
+    /// ```
+    /// # use gvdb::write::{GvdbFileWriter, GvdbHashTableBuilder};
+    /// let file = std::fs::File::open("test-data/gresource/json/test.json").unwrap();
+    /// let len = file.metadata().unwrap().len();
+    ///
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.insert_bytes_reader("test.json", file, len).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// GvdbFileWriter::new()
+    ///     .write_with_table(table_builder, &mut output)
+    ///     .unwrap();
+    /// ```
+    pub fn insert_bytes_reader(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        reader: impl Read + 'static,
+        len: u64,
+    ) -> GvdbBuilderResult<()> {
+        let item = GvdbBuilderItemValue::Stream(GvdbValueStream::bytes(reader, len));
+        self.insert_item_value(key, item)
+    }
+
+    /// Insert a value for `key` whose data is read when the file is written
+    #[cfg(feature = "gresource")]
+    pub(crate) fn insert_stream(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        stream: GvdbValueStream<'a>,
+    ) -> GvdbBuilderResult<()> {
+        self.insert_item_value(key, GvdbBuilderItemValue::Stream(stream))
+    }
+
+    /// Insert serialized GVariant data of type `signature` for `key`
+    ///
+    /// The data has to be in the byte order of this machine, like the data of
+    /// `g_variant_get_data()`. It is checked to be a value of type `signature` in normal form,
+    /// and then written as it is, or byteswapped if the file is written with a different byte
+    /// order.
+    ///
+    /// ```
+    /// # let mut table_builder = gvdb::write::GvdbHashTableBuilder::new();
+    /// table_builder
+    ///     .insert_raw("int", "u", 42u32.to_ne_bytes().to_vec())
+    ///     .unwrap();
+    /// assert!(table_builder.insert_raw("int", "u", vec![1, 2]).is_err());
+    /// ```
+    pub fn insert_raw(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        signature: &str,
+        bytes: impl Into<Cow<'a, [u8]>>,
+    ) -> GvdbBuilderResult<()> {
+        self.insert_raw_with_endianness(key, signature, bytes, GvdbEndianness::native())
+    }
+
+    /// Insert serialized GVariant data of type `signature` in the byte order `endianness` for
+    /// `key`
+    ///
+    /// See [`insert_raw`](Self::insert_raw) for details.
+    ///
+    /// ```
+    /// # use gvdb::write::{GvdbEndianness, GvdbHashTableBuilder};
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder
+    ///     .insert_raw_with_endianness("int", "u", &[0, 0, 0, 42][..], GvdbEndianness::Big)
+    ///     .unwrap();
+    /// ```
+    pub fn insert_raw_with_endianness(
+        &mut self,
+        key: &(impl ToString + ?Sized),
+        signature: &str,
+        bytes: impl Into<Cow<'a, [u8]>>,
+        endianness: GvdbEndianness,
+    ) -> GvdbBuilderResult<()> {
+        let bytes = bytes.into();
+        variant::validate(signature, &bytes).map_err(|err| {
+            GvdbWriterError::InvalidValue(format!(
+                "Data for key '{}' is not a valid value of type '{}': {}",
+                key.to_string(),
+                signature,
+                err
+            ))
+        })?;
+
+        let item = GvdbBuilderItemValue::Raw {
+            signature: signature.to_string(),
+            bytes,
+            endianness,
+        };
+        self.insert_item_value(key, item)
+    }
+
     /// Insert an entire hash table at `key`.
     ///
     /// ```
@@ -217,6 +564,38 @@ impl<'a> GvdbHashTableBuilder<'a> {
         self.insert_item_value(key, item)
     }
 
+    /// Remove the item at `key`. Returns whether the item existed.
+    ///
+    /// The key is removed from the children of its container. If the item is a container itself,
+    /// all items inside of it are removed as well.
+    ///
+    /// ```
+    /// # use gvdb::write::GvdbHashTableBuilder;
+    /// let mut table_builder = GvdbHashTableBuilder::new();
+    /// table_builder.insert_string("dir/string", "test").unwrap();
+    /// assert!(table_builder.remove("dir/"));
+    /// assert!(table_builder.is_empty());
+    /// ```
+    pub fn remove(&mut self, key: &str) -> bool {
+        let Some(item) = self.items.remove(key) else {
+            return false;
+        };
+
+        for value in self.items.values_mut() {
+            if let GvdbBuilderItemValue::Container(children) = value {
+                children.retain(|child| child != key);
+            }
+        }
+
+        if let GvdbBuilderItemValue::Container(children) = item {
+            for child in children {
+                self.remove(&child);
+            }
+        }
+
+        true
+    }
+
     /// The number of items contained in the hash table builder
     pub fn len(&self) -> usize {
         self.items.len()
@@ -228,7 +607,8 @@ impl<'a> GvdbHashTableBuilder<'a> {
     }
 
     pub(crate) fn build(mut self) -> GvdbBuilderResult<SimpleHashTable<'a>> {
-        let mut hash_table = SimpleHashTable::with_n_buckets(self.items.len());
+        let mut hash_table =
+            SimpleHashTable::with_layout(self.n_buckets(), self.n_bloom_words, self.bloom_shift);
 
         let mut keys: Vec<String> = self.items.keys().cloned().collect();
         keys.sort();
@@ -261,6 +641,32 @@ impl<'a> Default for GvdbHashTableBuilder<'a> {
     }
 }
 
+/// The data of a GVariant of type `v` that contains `bytes` of type `signature`
+fn variant_data(bytes: &[u8], signature: &str) -> Vec<u8> {
+    let mut data = Vec::with_capacity(bytes.len() + 1 + signature.len());
+    data.extend_from_slice(bytes);
+    data.push(0);
+    data.extend_from_slice(signature.as_bytes());
+    data
+}
+
+/// Serialize a value as GVariant of type `v` in the byte order `endianness`
+fn serialize_value(
+    value: &zvariant::Value,
+    endianness: GvdbEndianness,
+) -> GvdbBuilderResult<Vec<u8>> {
+    match endianness {
+        GvdbEndianness::Little => {
+            let context = zvariant::EncodingContext::<byteorder::LE>::new_gvariant(0);
+            Ok(zvariant::to_bytes(context, value)?)
+        }
+        GvdbEndianness::Big => {
+            let context = zvariant::EncodingContext::<byteorder::BE>::new_gvariant(0);
+            Ok(zvariant::to_bytes(context, value)?)
+        }
+    }
+}
+
 #[derive(Debug)]
 struct GvdbChunk {
     // The pointer that points to the data where the chunk will be in memory in the finished file
@@ -345,57 +751,89 @@ impl GvdbFileWriter {
             byteswap,
         };
 
-        this.allocate_empty_chunk(size_of::<GvdbHeader>(), 1);
+        // The header is at the start of the file, so its offsets always fit
+        let header = vec![0; size_of::<GvdbHeader>()].into_boxed_slice();
+        let pointer = GvdbPointer::new(0, header.len());
+        this.offset = header.len();
+        this.chunks.push_back(GvdbChunk::new(header, pointer));
         this
     }
 
-    /// Allocate a chunk
-    fn allocate_chunk_with_data(
-        &mut self,
-        data: Box<[u8]>,
-        alignment: usize,
-    ) -> (usize, &mut GvdbChunk) {
+    /// Reserve `size` bytes with the specified alignment at the end of the file
+    fn allocate(&mut self, size: usize, alignment: usize) -> GvdbBuilderResult<GvdbPointer> {
         // Align the data
-        self.offset = align_offset(self.offset, alignment);
+        let offset_start = align_offset(self.offset, alignment);
 
-        // Calculate the pointer
-        let offset_start = self.offset;
-        let offset_end = offset_start + data.len();
-        let pointer = GvdbPointer::new(offset_start, offset_end);
+        // All offsets in the file are 32 bit
+        let offset_end = offset_start
+            .checked_add(size)
+            .filter(|end| u32::try_from(*end).is_ok())
+            .ok_or_else(|| {
+                GvdbWriterError::FileSize(format!(
+                    "Data of {} bytes at offset {} exceeds the maximum file size of {} bytes",
+                    size,
+                    offset_start,
+                    u32::MAX
+                ))
+            })?;
 
         // Update the offset to the end of the chunk
         self.offset = offset_end;
+        Ok(GvdbPointer::new(offset_start, offset_end))
+    }
 
+    /// Allocate a chunk
+    fn allocate_chunk_with_data(
+        &mut self,
+        data: Box<[u8]>,
+        alignment: usize,
+    ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
+        let pointer = self.allocate(data.len(), alignment)?;
         let chunk = GvdbChunk::new(data, pointer);
         self.chunks.push_back(chunk);
         let index = self.chunks.len() - 1;
-        (index, &mut self.chunks[index])
+        Ok((index, &mut self.chunks[index]))
     }
 
-    fn allocate_empty_chunk(&mut self, size: usize, alignment: usize) -> (usize, &mut GvdbChunk) {
+    /// Allocate a chunk for a value that is read from its reader when the file is serialized
+    ///
+    /// The chunk itself stays empty, only its pointer covers the size of the value.
+    fn add_stream<'a>(
+        &mut self,
+        stream: GvdbValueStream<'a>,
+        streams: &mut HashMap<usize, GvdbValueStream<'a>>,
+    ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
+        let pointer = self.allocate(stream.size()?, 8)?;
+        self.chunks.push_back(GvdbChunk::new(Box::new([]), pointer));
+        let index = self.chunks.len() - 1;
+        streams.insert(index, stream);
+        Ok((index, &mut self.chunks[index]))
+    }
+
+    fn allocate_empty_chunk(
+        &mut self,
+        size: usize,
+        alignment: usize,
+    ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
         let data = vec![0; size].into_boxed_slice();
         self.allocate_chunk_with_data(data, alignment)
     }
 
-    fn add_value(&mut self, value: &zvariant::Value) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
-        #[cfg(target_endian = "little")]
-        let le = true;
-        #[cfg(target_endian = "big")]
-        let le = false;
-
-        let data = if le && !self.byteswap || !le && self.byteswap {
-            let context = zvariant::EncodingContext::<byteorder::LE>::new_gvariant(0);
-            zvariant::to_bytes(context, value)?.into_boxed_slice()
-        } else {
-            let context = zvariant::EncodingContext::<byteorder::BE>::new_gvariant(0);
-            zvariant::to_bytes(context, value)?.into_boxed_slice()
-        };
+    /// Serialize a value in the endianness of this writer
+    fn serialize_value(&self, value: &zvariant::Value) -> GvdbBuilderResult<Vec<u8>> {
+        serialize_value(value, GvdbEndianness::from_byteswapped(self.byteswap))
+    }
 
-        Ok(self.allocate_chunk_with_data(data, 8))
+    fn add_value(&mut self, value: &zvariant::Value) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
+        let data = self.serialize_value(value)?.into_boxed_slice();
+        self.allocate_chunk_with_data(data, 8)
     }
 
     #[cfg(feature = "glib")]
-    fn add_gvariant(&mut self, variant: &glib::Variant) -> (usize, &mut GvdbChunk) {
+    fn add_gvariant(
+        &mut self,
+        variant: &glib::Variant,
+    ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
         let value = if self.byteswap {
             glib::Variant::from_variant(&variant.byteswap())
         } else {
@@ -407,33 +845,64 @@ impl GvdbFileWriter {
         self.allocate_chunk_with_data(data.to_vec().into_boxed_slice(), 8)
     }
 
-    fn add_string(&mut self, string: &str) -> (usize, &mut GvdbChunk) {
+    /// Add serialized GVariant data of type `signature`, byteswapping it if its byte order
+    /// differs from the one of this writer
+    fn add_raw(
+        &mut self,
+        signature: &str,
+        bytes: &[u8],
+        endianness: GvdbEndianness,
+    ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
+        let mut data = variant_data(bytes, signature);
+        if endianness.is_byteswapped() != self.byteswap {
+            variant::byteswap(signature, &mut data[..bytes.len()])
+                .map_err(GvdbWriterError::InvalidValue)?;
+        }
+
+        self.allocate_chunk_with_data(data.into_boxed_slice(), 8)
+    }
+
+    fn add_string(&mut self, string: &str) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
         let data = string.to_string().into_boxed_str().into_boxed_bytes();
         self.allocate_chunk_with_data(data, 1)
     }
 
-    fn add_simple_hash_table(
+    fn add_simple_hash_table<'a>(
         &mut self,
-        table: SimpleHashTable,
+        table: SimpleHashTable<'a>,
+        streams: &mut HashMap<usize, GvdbValueStream<'a>>,
     ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
         for (index, (_bucket, item)) in table.iter().enumerate() {
             item.set_assigned_index(index as u32);
         }
 
-        let header = GvdbHashHeader::new(5, 0, table.n_buckets() as u32);
+        let n_buckets = u32::try_from(table.n_buckets()).map_err(|_| {
+            GvdbWriterError::HashTableLayout(format!(
+                "Too many hash buckets: {}",
+                table.n_buckets()
+            ))
+        })?;
+        let header = GvdbHashHeader::new(table.bloom_shift(), table.n_bloom_words(), n_buckets);
         let items_len = table.n_items() * size_of::<GvdbHashItem>();
         let size = size_of::<GvdbHashHeader>()
             + header.bloom_words_len()
             + header.buckets_len()
             + items_len;
 
-        let hash_buckets_offset = size_of::<GvdbHashHeader>() + header.bloom_words_len();
+        let bloom_words_offset = size_of::<GvdbHashHeader>();
+        let hash_buckets_offset = bloom_words_offset + header.bloom_words_len();
         let hash_items_offset = hash_buckets_offset + header.buckets_len();
 
-        let (hash_table_chunk_index, hash_table_chunk) = self.allocate_empty_chunk(size, 4);
+        let (hash_table_chunk_index, hash_table_chunk) = self.allocate_empty_chunk(size, 4)?;
         let header = transmute_one_to_bytes(&header);
         hash_table_chunk.data_mut()[0..header.len()].copy_from_slice(header);
 
+        for (index, word) in table.bloom_words().into_iter().enumerate() {
+            let start = bloom_words_offset + index * size_of::<u32>();
+            hash_table_chunk.data_mut()[start..start + size_of::<u32>()]
+                .copy_from_slice(&u32::to_le_bytes(word));
+        }
+
         let mut n_item = 0;
         for bucket in 0..table.n_buckets() {
             let hash_bucket_start = hash_buckets_offset + bucket * size_of::<u32>();
@@ -462,21 +931,29 @@ impl GvdbFileWriter {
                     )));
                 }
 
-                let key_ptr = self.add_string(key).1.pointer();
+                let key_ptr = self.add_string(key)?.1.pointer();
                 let typ = current_item.value_ref().typ();
 
                 let value_ptr = match current_item.value().take() {
                     GvdbBuilderItemValue::Value(value) => self.add_value(&value)?.1.pointer(),
                     #[cfg(feature = "glib")]
                     GvdbBuilderItemValue::GVariant(variant) => {
-                        self.add_gvariant(&variant).1.pointer()
+                        self.add_gvariant(&variant)?.1.pointer()
+                    }
+                    GvdbBuilderItemValue::Raw {
+                        signature,
+                        bytes,
+                        endianness,
+                    } => self.add_raw(&signature, &bytes, endianness)?.1.pointer(),
+                    GvdbBuilderItemValue::Stream(stream) => {
+                        self.add_stream(stream, streams)?.1.pointer()
                     }
                     GvdbBuilderItemValue::TableBuilder(tb) => {
-                        self.add_table_builder(tb)?.1.pointer()
+                        self.add_table_builder(tb, streams)?.1.pointer()
                     }
                     GvdbBuilderItemValue::Container(children) => {
                         let size = children.len() * size_of::<u32>();
-                        let chunk = self.allocate_empty_chunk(size, 4).1;
+                        let chunk = self.allocate_empty_chunk(size, 4)?.1;
 
                         let mut offset = 0;
                         for child in children {
@@ -520,20 +997,23 @@ impl GvdbFileWriter {
         ))
     }
 
-    fn add_table_builder(
+    fn add_table_builder<'a>(
         &mut self,
-        table_builder: GvdbHashTableBuilder,
+        table_builder: GvdbHashTableBuilder<'a>,
+        streams: &mut HashMap<usize, GvdbValueStream<'a>>,
     ) -> GvdbBuilderResult<(usize, &mut GvdbChunk)> {
-        self.add_simple_hash_table(table_builder.build()?)
+        self.add_simple_hash_table(table_builder.build()?, streams)
     }
 
     fn file_size(&self) -> usize {
         self.chunks[self.chunks.len() - 1].pointer().end() as usize
     }
 
+    /// Write all chunks into `writer`, the data of values in `streams` is read from their readers
     fn serialize(
         mut self,
         root_chunk_index: usize,
+        mut streams: HashMap<usize, GvdbValueStream>,
         writer: &mut dyn Write,
     ) -> GvdbBuilderResult<usize> {
         let root_ptr = self
@@ -551,7 +1031,7 @@ impl GvdbFileWriter {
             .copy_from_slice(transmute_one_to_bytes(&header));
 
         let mut size = 0;
-        for chunk in self.chunks.into_iter() {
+        for (index, chunk) in self.chunks.into_iter().enumerate() {
             // Align
             if size < chunk.pointer().start() as usize {
                 let padding = chunk.pointer().start() as usize - size;
@@ -560,26 +1040,76 @@ impl GvdbFileWriter {
             }
 
             size += chunk.pointer().size();
-            writer.write_all(&chunk.into_data())?;
+            if let Some(stream) = streams.remove(&index) {
+                stream.write(writer, self.byteswap)?;
+            } else {
+                writer.write_all(&chunk.into_data())?;
+            }
         }
 
         Ok(size)
     }
 
-    fn serialize_to_vec(self, root_chunk_index: usize) -> GvdbBuilderResult<Vec<u8>> {
+    fn serialize_to_vec(
+        self,
+        root_chunk_index: usize,
+        streams: HashMap<usize, GvdbValueStream>,
+    ) -> GvdbBuilderResult<Vec<u8>> {
         let mut vec = Vec::with_capacity(self.file_size());
-        self.serialize(root_chunk_index, &mut vec)?;
+        self.serialize(root_chunk_index, streams, &mut vec)?;
         Ok(vec)
     }
 
     /// Write the GVDB file into the provided [`std::io::Write`]
+    ///
+    /// The layout of the file is calculated before anything is written. Values that were inserted
+    /// with [`GvdbHashTableBuilder::insert_bytes_reader`] are then copied from their readers
+    /// directly into `writer`.
     pub fn write_with_table(
         mut self,
         table_builder: GvdbHashTableBuilder,
         writer: &mut dyn Write,
     ) -> GvdbBuilderResult<usize> {
-        let index = self.add_table_builder(table_builder)?.0;
-        self.serialize(index, writer)
+        let mut streams = HashMap::new();
+        let index = self.add_table_builder(table_builder, &mut streams)?.0;
+        self.serialize(index, streams, writer)
+    }
+
+    /// Write the GVDB file into a new memfd and seal it against any further modification
+    ///
+    /// The memfd can be passed to other processes, which can map it safely with
+    /// [`GvdbFile::from_sealed_memfd`](crate::read::GvdbFile::from_sealed_memfd).
+    #[cfg(all(feature = "memfd", target_os = "linux"))]
+    pub fn write_to_sealed_memfd(
+        mut self,
+        table_builder: GvdbHashTableBuilder,
+    ) -> GvdbBuilderResult<std::os::unix::io::OwnedFd> {
+        use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+        let mut streams = HashMap::new();
+        let index = self.add_table_builder(table_builder, &mut streams)?.0;
+
+        let fd = unsafe {
+            libc::memfd_create(
+                b"gvdb\0".as_ptr().cast(),
+                libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+            )
+        };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        // Safety: memfd_create returned a new file descriptor that nothing else owns
+        let mut file = std::fs::File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+        self.serialize(index, streams, &mut file)?;
+
+        let seals =
+            libc::F_SEAL_SEAL | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE;
+        if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_ADD_SEALS, seals) } < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(file.into())
     }
 
     /// Create a [`Vec<u8>`] with the GVDB file data
@@ -587,8 +1117,127 @@ impl GvdbFileWriter {
         mut self,
         table_builder: GvdbHashTableBuilder,
     ) -> GvdbBuilderResult<Vec<u8>> {
-        let index = self.add_table_builder(table_builder)?.0;
-        self.serialize_to_vec(index)
+        let mut streams = HashMap::new();
+        let index = self.add_table_builder(table_builder, &mut streams)?.0;
+        self.serialize_to_vec(index, streams)
+    }
+
+    /// Byteswap the values of all items in the hash table at `pointer` and its nested tables
+    fn convert_table(
+        &self,
+        file: &GvdbFile,
+        pointer: &GvdbPointer,
+        data: &mut [u8],
+        visited: &mut HashSet<u32>,
+        values: &mut BTreeMap<usize, usize>,
+        depth: usize,
+    ) -> GvdbBuilderResult<()> {
+        // Guard against hash tables that contain themselves
+        if !visited.insert(pointer.start()) {
+            return Ok(());
+        }
+
+        file.limits.check_depth(depth)?;
+
+        let table = GvdbHashTable::for_bytes(file.dereference(pointer, 4)?, file)?;
+        for index in 0..table.table.n_hash_items() {
+            let item = table.table.get_hash_item_for_index(index)?;
+            let value_ptr = item.value_ptr();
+
+            match item.typ()? {
+                GvdbHashItemType::Value => {
+                    // Check the bounds and alignment of the value
+                    let len = file.dereference(value_ptr, 8)?.len();
+                    let start = value_ptr.start() as usize;
+                    let end = start + len;
+
+                    // Values that are shared between items must only be converted once
+                    if values.get(&start) == Some(&end) {
+                        continue;
+                    }
+                    if let Some((&other_start, &other_end)) = values.range(..end).next_back() {
+                        if other_end > start {
+                            return Err(GvdbWriterError::InvalidValue(format!(
+                                "The value of key '{}' overlaps with the value at {}..{}",
+                                file.get_key(&item).unwrap_or_default(),
+                                other_start,
+                                other_end
+                            )));
+                        }
+                    }
+                    values.insert(start, end);
+
+                    variant::byteswap("v", &mut data[start..end]).map_err(|err| {
+                        GvdbWriterError::InvalidValue(format!(
+                            "The value of key '{}' can't be converted: {}",
+                            file.get_key(&item).unwrap_or_default(),
+                            err
+                        ))
+                    })?;
+                }
+                GvdbHashItemType::HashTable => {
+                    self.convert_table(file, value_ptr, data, visited, values, depth + 1)?;
+                }
+                GvdbHashItemType::Container => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Convert an existing GVDB file to the endianness of this writer
+    ///
+    /// Hash tables and items are stored in little endian byte order in every GVDB file, so only
+    /// the file signature and the values of all items, including those in nested hash tables,
+    /// are byteswapped. The layout of the file is kept exactly as it is. If the file already has
+    /// the requested endianness, its data is copied unchanged. Values shared by several items are
+    /// converted once, values that partially overlap fail with [`GvdbWriterError::InvalidValue`].
+    ///
+    /// ```
+    /// use gvdb::read::GvdbFile;
+    /// use gvdb::write::GvdbFileWriter;
+    ///
+    /// // test2.gvdb is a big endian file
+    /// let path = std::path::PathBuf::from("test-data/test2.gvdb");
+    /// let file = GvdbFile::from_file(&path).unwrap();
+    /// let data = GvdbFileWriter::new().write_to_vec_with_file(&file).unwrap();
+    ///
+    /// let converted = GvdbFile::from_bytes(data.into()).unwrap();
+    /// let root = converted.hash_table().unwrap();
+    /// let table = root.get_hash_table("table").unwrap();
+    /// assert_eq!(table.get::<u32>("int").unwrap(), 42);
+    /// ```
+    pub fn write_to_vec_with_file(self, file: &GvdbFile) -> GvdbBuilderResult<Vec<u8>> {
+        let mut data = file.data.as_ref().to_vec();
+        if file.byteswapped == self.byteswap {
+            return Ok(data);
+        }
+
+        let header = file.get_header()?;
+        // The signature is the only part of the header that depends on the endianness
+        let new_header = GvdbHeader::new(self.byteswap, 0, GvdbPointer::NULL);
+        let signature_size = size_of::<[u32; 2]>();
+        data[..signature_size]
+            .copy_from_slice(&transmute_one_to_bytes(&new_header)[..signature_size]);
+
+        let mut visited = HashSet::new();
+        let mut values = BTreeMap::new();
+        self.convert_table(file, header.root(), &mut data, &mut visited, &mut values, 0)?;
+        Ok(data)
+    }
+
+    /// Convert an existing GVDB file to the endianness of this writer and write it into the
+    /// provided [`std::io::Write`]
+    ///
+    /// See [`write_to_vec_with_file`](Self::write_to_vec_with_file) for details.
+    pub fn write_with_file(
+        self,
+        file: &GvdbFile,
+        writer: &mut dyn Write,
+    ) -> GvdbBuilderResult<usize> {
+        let data = self.write_to_vec_with_file(file)?;
+        writer.write_all(&data)?;
+        Ok(data.len())
     }
 }
 
@@ -607,8 +1256,9 @@ mod test {
     use std::io::Cursor;
 
     use crate::test::{
-        assert_bytes_eq, assert_is_file_1, assert_is_file_2, byte_compare_file_1,
-        byte_compare_file_2,
+        assert_bytes_eq, assert_is_file_1, assert_is_file_2, assert_is_file_3, byte_compare_file,
+        byte_compare_file_1, byte_compare_file_2, byte_compare_file_3, new_simple_file,
+        TEST_FILE_1, TEST_FILE_2, TEST_FILE_3,
     };
     #[allow(unused_imports)]
     use pretty_assertions::{assert_eq, assert_ne, assert_str_eq};
@@ -680,6 +1330,56 @@ mod test {
         builder.insert_string("string/test", "test").unwrap();
     }
 
+    #[test]
+    fn hash_table_builder_layout() {
+        let mut builder = GvdbHashTableBuilder::new();
+        assert_eq!(builder.n_buckets(), 0);
+        builder.set_n_buckets(Some(0)).unwrap();
+        assert_eq!(builder.n_buckets(), 0);
+
+        for num in 0..10 {
+            builder.insert_string(&format!("{}", num), "test").unwrap();
+        }
+        assert_eq!(builder.n_buckets(), 1);
+        builder.set_n_buckets(Some(20)).unwrap();
+        assert_eq!(builder.n_buckets(), 20);
+        #[cfg(target_pointer_width = "64")]
+        {
+            let err = builder.set_n_buckets(Some(1 << 32)).unwrap_err();
+            assert_matches!(err, GvdbWriterError::HashTableLayout(_));
+            assert_eq!(builder.n_buckets(), 20);
+        }
+        builder.set_n_buckets(None).unwrap();
+        assert_eq!(builder.n_buckets(), 10);
+
+        builder.set_load_factor(4.0).unwrap();
+        assert_eq!(builder.n_buckets(), 3);
+        for load_factor in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            let err = builder.set_load_factor(load_factor).unwrap_err();
+            assert_matches!(err, GvdbWriterError::HashTableLayout(_));
+        }
+
+        let err = builder.set_bloom_filter(1 << 27, 5).unwrap_err();
+        assert_matches!(err, GvdbWriterError::HashTableLayout(_));
+        let err = builder.set_bloom_filter(1, 32).unwrap_err();
+        assert_matches!(err, GvdbWriterError::HashTableLayout(_));
+
+        let estimate = builder.layout_estimate();
+        assert_eq!(estimate.n_items, 10);
+        assert_eq!(estimate.n_buckets, 3);
+        assert_eq!(estimate.bloom_false_positive_rate, 1.0);
+        assert_eq!(estimate.size, 8 + 3 * 4 + 10 * 24);
+
+        builder.set_bloom_filter(8, 5).unwrap();
+        let estimate = builder.layout_estimate();
+        assert!(estimate.bloom_false_positive_rate < 1.0);
+        assert_eq!(estimate.size, 8 + 8 * 4 + 3 * 4 + 10 * 24);
+
+        let table = builder.build().unwrap();
+        assert_eq!(table.n_buckets(), 3);
+        assert_eq!(table.n_bloom_words(), 8);
+    }
+
     #[test]
     fn file_builder_file_1() {
         let mut file_builder = GvdbFileWriter::new();
@@ -691,8 +1391,12 @@ mod test {
         let tuple_data = (value1, value2, value3);
         let variant = zvariant::Value::new(tuple_data);
         table_builder.insert_value("root_key", variant).unwrap();
-        let root_index = file_builder.add_table_builder(table_builder).unwrap().0;
-        let bytes = file_builder.serialize_to_vec(root_index).unwrap();
+        let mut streams = HashMap::new();
+        let root_index = file_builder
+            .add_table_builder(table_builder, &mut streams)
+            .unwrap()
+            .0;
+        let bytes = file_builder.serialize_to_vec(root_index, streams).unwrap();
         let root = GvdbFile::from_bytes(Cow::Owned(bytes)).unwrap();
         assert_is_file_1(&root);
         byte_compare_file_1(&root);
@@ -713,8 +1417,12 @@ mod test {
         table_builder
             .insert_table("table", table_builder_2)
             .unwrap();
-        let root_index = file_builder.add_table_builder(table_builder).unwrap().0;
-        let bytes = file_builder.serialize_to_vec(root_index).unwrap();
+        let mut streams = HashMap::new();
+        let root_index = file_builder
+            .add_table_builder(table_builder, &mut streams)
+            .unwrap()
+            .0;
+        let bytes = file_builder.serialize_to_vec(root_index, streams).unwrap();
         let root = GvdbFile::from_bytes(Cow::Owned(bytes)).unwrap();
         assert_is_file_2(&root);
         byte_compare_file_2(&root);
@@ -752,8 +1460,12 @@ mod test {
         let tuple_data = (value1, value2, value3);
         let variant = zvariant::Value::new(tuple_data);
         table_builder.insert_value("root_key", variant).unwrap();
-        let root_index = file_builder.add_table_builder(table_builder).unwrap().0;
-        let bytes = file_builder.serialize_to_vec(root_index).unwrap();
+        let mut streams = HashMap::new();
+        let root_index = file_builder
+            .add_table_builder(table_builder, &mut streams)
+            .unwrap()
+            .0;
+        let bytes = file_builder.serialize_to_vec(root_index, streams).unwrap();
 
         // "GVariant" byteswapped at 32 bit boundaries is the header for big-endian GVariant files
         assert_eq!("raVGtnai", std::str::from_utf8(&bytes[0..8]).unwrap());
@@ -762,11 +1474,161 @@ mod test {
         assert_is_file_1(&root);
     }
 
+    #[test]
+    fn convert_endianness() {
+        // Converting a big endian file to little endian and back yields the same data
+        let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
+        let data = GvdbFileWriter::new().write_to_vec_with_file(&file).unwrap();
+        assert_ne!(data, file.data.as_ref());
+        let converted = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        assert_is_file_2(&converted);
+
+        let mut cursor = Cursor::new(Vec::new());
+        let size = GvdbFileWriter::for_big_endian()
+            .write_with_file(&converted, &mut cursor)
+            .unwrap();
+        assert_eq!(size, cursor.get_ref().len());
+        let roundtrip = GvdbFile::from_bytes(Cow::Owned(cursor.into_inner())).unwrap();
+        byte_compare_file_2(&roundtrip);
+
+        // A converted file is identical to one that was written in the target endianness
+        let little = new_simple_file(false);
+        let big = new_simple_file(true);
+        let data = GvdbFileWriter::for_big_endian()
+            .write_to_vec_with_file(&little)
+            .unwrap();
+        assert_bytes_eq(&data, big.data.as_ref(), "Converting to big endian");
+
+        // Converting to the same endianness copies the data
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_file(&little)
+            .unwrap();
+        assert_bytes_eq(&data, little.data.as_ref(), "Converting to little endian");
+    }
+
+    #[test]
+    fn convert_endianness_gresource() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let data = GvdbFileWriter::for_big_endian()
+            .write_to_vec_with_file(&file)
+            .unwrap();
+        let converted = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        assert_is_file_3(&converted);
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_file(&converted)
+            .unwrap();
+        let roundtrip = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        byte_compare_file_3(&roundtrip);
+    }
+
+    #[test]
+    fn convert_endianness_invalid() {
+        let mut data = new_simple_file(false).data.as_ref().to_vec();
+        // Point the root table outside of the file
+        data[16..24].copy_from_slice(&[0xff; 8]);
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let res = GvdbFileWriter::for_big_endian().write_to_vec_with_file(&file);
+        assert_matches!(res, Err(GvdbWriterError::Read(_)));
+
+        // A value with an invalid type signature can't be byteswapped
+        let file = new_simple_file(false);
+        let item = file.hash_table().unwrap().get_hash_item("test").unwrap();
+        let mut data = file.data.as_ref().to_vec();
+        data[item.value_ptr().end() as usize - 1] = b'z';
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let res = GvdbFileWriter::for_big_endian().write_to_vec_with_file(&file);
+        assert_matches!(res, Err(GvdbWriterError::InvalidValue(_)));
+    }
+
+    #[test]
+    fn convert_endianness_shared_values() {
+        let mut table_builder = GvdbHashTableBuilder::new();
+        table_builder.insert("a", 1u32).unwrap();
+        table_builder.insert("b", 2u32).unwrap();
+        table_builder.insert("c", "string").unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(
+            GvdbFileWriter::new()
+                .write_to_vec_with_table(table_builder)
+                .unwrap(),
+        ))
+        .unwrap();
+        let table = file.hash_table().unwrap();
+        let pointer = |key| *table.get_hash_item(key).unwrap().value_ptr();
+        let pointer_bytes = |pointer: GvdbPointer| {
+            [pointer.start().to_le_bytes(), pointer.end().to_le_bytes()].concat()
+        };
+        let set_pointer = |data: &mut [u8], key, new: GvdbPointer| {
+            let old = pointer_bytes(pointer(key));
+            let offset = data
+                .windows(old.len())
+                .position(|window| window == old)
+                .unwrap();
+            data[offset..offset + old.len()].copy_from_slice(&pointer_bytes(new));
+        };
+
+        // Two items that share the same value data
+        let mut data = file.data.as_ref().to_vec();
+        set_pointer(&mut data, "b", pointer("a"));
+        let shared = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let data = GvdbFileWriter::for_big_endian()
+            .write_to_vec_with_file(&shared)
+            .unwrap();
+        let converted = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let root = converted.hash_table().unwrap();
+        assert_eq!(root.get::<u32>("a").unwrap(), 1);
+        assert_eq!(root.get::<u32>("b").unwrap(), 1);
+        assert_eq!(root.get::<String>("c").unwrap(), "string");
+
+        // Values that partially overlap can't be converted
+        let mut data = file.data.as_ref().to_vec();
+        let c = pointer("c");
+        set_pointer(
+            &mut data,
+            "a",
+            GvdbPointer::new(c.start() as usize, c.end() as usize - 1),
+        );
+        let overlapping = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let res = GvdbFileWriter::for_big_endian().write_to_vec_with_file(&overlapping);
+        assert_matches!(res, Err(GvdbWriterError::InvalidValue(msg)) if msg.contains("overlaps"));
+    }
+
+    #[cfg(all(feature = "memfd", target_os = "linux"))]
+    #[test]
+    fn sealed_memfd() {
+        use std::io::{Read, Seek};
+        use std::os::unix::io::AsRawFd;
+
+        let mut table = GvdbHashTableBuilder::new();
+        table.insert_string("string", "test string").unwrap();
+        let bytes = GvdbFileWriter::new()
+            .write_to_vec_with_table(table)
+            .unwrap();
+
+        let mut table = GvdbHashTableBuilder::new();
+        table.insert_string("string", "test string").unwrap();
+        let fd = GvdbFileWriter::new().write_to_sealed_memfd(table).unwrap();
+
+        let seals = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GET_SEALS) };
+        let expected =
+            libc::F_SEAL_SEAL | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE;
+        assert_eq!(seals & expected, expected);
+
+        let mut memfd = std::fs::File::from(fd);
+        memfd.rewind().unwrap();
+        let mut data = Vec::new();
+        memfd.read_to_end(&mut data).unwrap();
+        assert_bytes_eq(&bytes, &data, "memfd contents");
+
+        // Writing fails
+        assert!(std::io::Write::write_all(&mut memfd, b"test").is_err());
+    }
+
     #[test]
     fn missing_root() {
         let file = GvdbFileWriter::new();
         assert_matches!(
-            file.serialize_to_vec(1),
+            file.serialize_to_vec(1, HashMap::new()),
             Err(GvdbWriterError::Consistency(_))
         );
     }
@@ -809,10 +1671,270 @@ mod test {
         table.remove("test/test");
 
         let mut file = GvdbFileWriter::new();
-        let err = file.add_simple_hash_table(table).unwrap_err();
+        let err = file
+            .add_simple_hash_table(table, &mut HashMap::new())
+            .unwrap_err();
         assert_matches!(err, GvdbWriterError::Consistency(_))
     }
 
+    #[test]
+    fn from_table() {
+        for path in [&*TEST_FILE_1, &*TEST_FILE_2] {
+            let file = GvdbFile::from_file(path).unwrap();
+            let table_builder =
+                GvdbHashTableBuilder::from_table(&file.hash_table().unwrap()).unwrap();
+
+            let writer = if file.byteswapped {
+                GvdbFileWriter::for_big_endian()
+            } else {
+                GvdbFileWriter::new()
+            };
+            let data = writer.write_to_vec_with_table(table_builder).unwrap();
+            let copy = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+            byte_compare_file(&copy, path);
+        }
+
+        // GLib orders the items of test3.gresource differently, compare the structure instead
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let table_builder = GvdbHashTableBuilder::from_table(&file.hash_table().unwrap()).unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        byte_compare_file_3(&GvdbFile::from_bytes(Cow::Owned(data)).unwrap());
+
+        // test2.gvdb is big endian, the values are byteswapped when writing little endian
+        let file = GvdbFile::from_file(&TEST_FILE_2).unwrap();
+        let table_builder = GvdbHashTableBuilder::from_table(&file.hash_table().unwrap()).unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let copy = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        assert!(!copy.byteswapped);
+        assert_is_file_2(&copy);
+
+        let root = copy.hash_table().unwrap();
+        let original = file.hash_table().unwrap();
+        assert_eq!(
+            root.get_item_info("string").unwrap().value_bytes(),
+            original.get_item_info("string").unwrap().value_bytes()
+        );
+        let table = root.get_hash_table("table").unwrap();
+        let info = table.get_item_info("int").unwrap();
+        assert_eq!(info.value_bytes(), Some(&42u32.to_le_bytes()[..]));
+        let original = original.get_hash_table("table").unwrap();
+        let original = original.get_item_info("int").unwrap();
+        assert_eq!(original.value_bytes(), Some(&42u32.to_be_bytes()[..]));
+    }
+
+    #[test]
+    fn from_table_modify() {
+        let file = GvdbFile::from_file(&TEST_FILE_3).unwrap();
+        let mut table_builder =
+            GvdbHashTableBuilder::from_table(&file.hash_table().unwrap()).unwrap();
+        let n_items = table_builder.len();
+
+        assert!(table_builder.remove("/gvdb/rs/test/icons/"));
+        assert!(!table_builder.remove("/gvdb/rs/test/icons/"));
+        assert_eq!(table_builder.len(), n_items - 4);
+        table_builder
+            .insert_string("/gvdb/rs/test/new", "value")
+            .unwrap();
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let copy = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let table = copy.hash_table().unwrap();
+        assert!(!table.contains_key("/gvdb/rs/test/icons/scalable/actions/send-symbolic.svg"));
+        assert_eq!(table.get::<String>("/gvdb/rs/test/new").unwrap(), "value");
+
+        let original = file.hash_table().unwrap();
+        let key = "/gvdb/rs/test/online-symbolic.svg";
+        assert_eq!(
+            table.get_item_info(key).unwrap().bytes(),
+            original.get_item_info(key).unwrap().bytes()
+        );
+
+        let item = copy.lookup_path(["", "gvdb", "rs", "test"]).unwrap();
+        let crate::read::GvdbPathItem::Container(children) = item else {
+            panic!("Expected a container");
+        };
+        assert!(children.contains(&"new".to_string()));
+        assert!(!children.contains(&"icons/".to_string()));
+    }
+
+    #[test]
+    fn from_table_path_separator() {
+        let mut table_builder = GvdbHashTableBuilder::with_path_separator(Some(":"));
+        table_builder.insert_string("a:b", "b").unwrap();
+        let mut sub_table_builder = GvdbHashTableBuilder::with_path_separator(Some(":"));
+        sub_table_builder.insert_string("c:d", "d").unwrap();
+        table_builder
+            .insert_table("table", sub_table_builder)
+            .unwrap();
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+
+        let mut table_builder = GvdbHashTableBuilder::from_table_with_path_separator(
+            &file.hash_table().unwrap(),
+            Some(":"),
+        )
+        .unwrap();
+        table_builder.insert_string("a:e", "e").unwrap();
+        let GvdbBuilderItemValue::TableBuilder(sub_table_builder) =
+            table_builder.items.get_mut("table").unwrap()
+        else {
+            panic!("Expected a hash table");
+        };
+        sub_table_builder.insert_string("c:f", "f").unwrap();
+
+        let data = GvdbFileWriter::new()
+            .write_to_vec_with_table(table_builder)
+            .unwrap();
+        let copy = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let table = copy.hash_table().unwrap();
+        let children = |item| {
+            let crate::read::GvdbPathItem::Container(mut children) = item else {
+                panic!("Expected a container");
+            };
+            children.sort();
+            children
+        };
+        let item = table.lookup_path_with_separator("a", ":").unwrap();
+        assert_eq!(children(item), ["b", "e"]);
+        let item = table.lookup_path(["table", "c:"]).unwrap();
+        assert_eq!(children(item), ["d", "f"]);
+    }
+
+    #[test]
+    fn insert_raw() {
+        let value = zvariant::Value::new((42u32, "test", vec![1u8, 2, 3]));
+        let le = zvariant::to_bytes(
+            zvariant::EncodingContext::<byteorder::LE>::new_gvariant(0),
+            &value,
+        )
+        .unwrap();
+        let be = zvariant::to_bytes(
+            zvariant::EncodingContext::<byteorder::BE>::new_gvariant(0),
+            &value,
+        )
+        .unwrap();
+        // The serialized `v` ends with the type signature of the value
+        let le = &le[..le.len() - 7];
+        let be = &be[..be.len() - 7];
+
+        for writer in [GvdbFileWriter::new(), GvdbFileWriter::for_big_endian()] {
+            let mut table = GvdbHashTableBuilder::new();
+            table
+                .insert_raw_with_endianness("le", "(usay)", le, GvdbEndianness::Little)
+                .unwrap();
+            table
+                .insert_raw_with_endianness("be", "(usay)", be, GvdbEndianness::Big)
+                .unwrap();
+            table
+                .insert_raw("int", "u", 7u32.to_ne_bytes().to_vec())
+                .unwrap();
+
+            let data = writer.write_to_vec_with_table(table).unwrap();
+            let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get_value("le").unwrap(), value);
+            assert_eq!(table.get_value("be").unwrap(), value);
+            assert_eq!(table.get::<u32>("int").unwrap(), 7);
+
+            // Data in the byte order of the file is written verbatim
+            let key = if file.byteswapped == cfg!(target_endian = "little") {
+                "be"
+            } else {
+                "le"
+            };
+            let expected = if key == "be" { be } else { le };
+            let info = table.get_item_info(key).unwrap();
+            assert_eq!(info.value_bytes().unwrap(), expected);
+        }
+
+        // {'a': <uint16 1>} as serialized by GLib, which zvariant can't serialize the same way
+        let dict = b"a\0\0\0\0\0\0\0\x01\0\0q\x02\x0d";
+        let mut table = GvdbHashTableBuilder::new();
+        table
+            .insert_raw_with_endianness("dict", "a{sv}", &dict[..], GvdbEndianness::Little)
+            .unwrap();
+        let data = GvdbFileWriter::for_big_endian()
+            .write_to_vec_with_table(table)
+            .unwrap();
+        let file = GvdbFile::from_bytes(Cow::Owned(data)).unwrap();
+        let info = file.hash_table().unwrap().get_item_info("dict").unwrap();
+        assert_eq!(
+            info.value_bytes().unwrap(),
+            b"a\0\0\0\0\0\0\0\0\x01\0q\x02\x0d"
+        );
+
+        let mut table = GvdbHashTableBuilder::new();
+        for (signature, bytes) in [
+            ("u", &[1u8, 2][..]),
+            ("u", &[1u8, 2, 3, 4, 5][..]),
+            ("s", &b"test"[..]),
+            ("", &[][..]),
+            ("uu", &[0; 8][..]),
+            ("(u", &[0; 4][..]),
+            ("b", &[2][..]),
+            ("(yu)", &[1, 1, 0, 0, 42, 0, 0, 0][..]),
+        ] {
+            let err = table.insert_raw("invalid", signature, bytes).unwrap_err();
+            assert_matches!(err, GvdbWriterError::InvalidValue(_));
+        }
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn bytes_reader() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| i as u8).collect();
+
+        for writer in [GvdbFileWriter::new(), GvdbFileWriter::for_big_endian()] {
+            let mut table = GvdbHashTableBuilder::new();
+            table.insert_string("dir/string", "test").unwrap();
+            table
+                .insert_bytes_reader("dir/bytes", Cursor::new(data.clone()), data.len() as u64)
+                .unwrap();
+            table.insert("int", 42u32).unwrap();
+
+            let mut output = Vec::new();
+            let size = writer.write_with_table(table, &mut output).unwrap();
+            assert_eq!(size, output.len());
+
+            let file = GvdbFile::from_bytes(Cow::Owned(output)).unwrap();
+            let table = file.hash_table().unwrap();
+            assert_eq!(table.get::<Vec<u8>>("dir/bytes").unwrap(), data);
+            assert_eq!(table.get::<String>("dir/string").unwrap(), "test");
+            assert_eq!(table.get::<u32>("int").unwrap(), 42);
+        }
+
+        // The reader ends before the announced length
+        let mut table = GvdbHashTableBuilder::new();
+        table
+            .insert_bytes_reader("bytes", Cursor::new(data.clone()), data.len() as u64 + 1)
+            .unwrap();
+        let err = GvdbFileWriter::new()
+            .write_to_vec_with_table(table)
+            .unwrap_err();
+        assert_matches!(err, GvdbWriterError::Io(_, None));
+
+        // Offsets in the file are 32 bit, the data is never read
+        let mut table = GvdbHashTableBuilder::new();
+        table
+            .insert_bytes_reader("first", std::io::repeat(0), 3 << 30)
+            .unwrap();
+        table
+            .insert_bytes_reader("second", std::io::repeat(0), 3 << 30)
+            .unwrap();
+        let err = GvdbFileWriter::new()
+            .write_with_table(table, &mut std::io::sink())
+            .unwrap_err();
+        assert_matches!(err, GvdbWriterError::FileSize(_));
+    }
+
     #[test]
     fn io_error() {
         let file = GvdbFileWriter::default();
@@ -2,20 +2,42 @@ use crate::util::djb_hash;
 use crate::write::item::{GvdbBuilderItem, GvdbBuilderItemValue};
 use std::rc::Rc;
 
+/// The bloom shift GLib uses for all hash tables it writes
+pub const DEFAULT_BLOOM_SHIFT: u32 = 5;
+
+/// Set the two bits for `hash_value` in the bloom filter `words`
+///
+/// This is the counterpart of the bloom filter check in the reader
+pub fn bloom_insert(words: &mut [u32], bloom_shift: u32, hash_value: u32) {
+    let word = (hash_value / 32) as usize % words.len();
+    let mut mask = 1 << (hash_value & 31);
+    mask |= 1 << ((hash_value >> bloom_shift) & 31);
+    words[word] |= mask;
+}
+
 #[derive(Debug)]
 pub struct SimpleHashTable<'a> {
     buckets: Vec<Option<Rc<GvdbBuilderItem<'a>>>>,
     n_items: usize,
+    n_bloom_words: u32,
+    bloom_shift: u32,
 }
 
 impl<'a> SimpleHashTable<'a> {
+    #[cfg(test)]
     pub fn with_n_buckets(n_buckets: usize) -> Self {
+        Self::with_layout(n_buckets, 0, DEFAULT_BLOOM_SHIFT)
+    }
+
+    pub fn with_layout(n_buckets: usize, n_bloom_words: u32, bloom_shift: u32) -> Self {
         let mut buckets = Vec::with_capacity(n_buckets);
         buckets.resize_with(n_buckets, || None);
 
         Self {
             buckets,
             n_items: 0,
+            n_bloom_words,
+            bloom_shift,
         }
     }
 
@@ -23,6 +45,26 @@ impl<'a> SimpleHashTable<'a> {
         self.buckets.len()
     }
 
+    pub fn n_bloom_words(&self) -> u32 {
+        self.n_bloom_words
+    }
+
+    pub fn bloom_shift(&self) -> u32 {
+        self.bloom_shift
+    }
+
+    /// Calculate the bloom filter words for all items in this table
+    pub fn bloom_words(&self) -> Vec<u32> {
+        let mut words = vec![0u32; self.n_bloom_words as usize];
+        if !words.is_empty() {
+            for (_, item) in self.iter() {
+                bloom_insert(&mut words, self.bloom_shift, item.hash());
+            }
+        }
+
+        words
+    }
+
     pub fn n_items(&self) -> usize {
         self.n_items
     }
@@ -200,6 +242,7 @@ impl<'it, 'h> Iterator for SimpleHashTableIter<'it, 'h> {
 
 #[cfg(test)]
 mod test {
+    use crate::util::djb_hash;
     use crate::write::hash::SimpleHashTable;
     use crate::write::item::GvdbBuilderItemValue;
 
@@ -257,4 +300,25 @@ mod test {
 
         assert!(!table.remove("50"));
     }
+
+    #[test]
+    fn bloom_words() {
+        let mut table: SimpleHashTable = SimpleHashTable::with_n_buckets(4);
+        table.insert("test", zvariant::Value::new(1).into());
+        assert!(table.bloom_words().is_empty());
+
+        let mut table: SimpleHashTable = SimpleHashTable::with_layout(4, 2, 5);
+        for index in 0..20 {
+            table.insert(&format!("{}", index), zvariant::Value::new(index).into());
+        }
+
+        let words = table.bloom_words();
+        assert_eq!(words.len(), 2);
+        for index in 0..20 {
+            let hash_value = djb_hash(&format!("{}", index));
+            let word = words[(hash_value / 32) as usize % words.len()];
+            let mask = 1 << (hash_value & 31) | 1 << ((hash_value >> 5) & 31);
+            assert_eq!(word & mask, mask);
+        }
+    }
 }
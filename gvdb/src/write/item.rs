@@ -1,8 +1,51 @@
 use crate::read::GvdbHashItemType;
 use crate::write::file::GvdbHashTableBuilder;
+use crate::write::stream::GvdbValueStream;
+use std::borrow::Cow;
 use std::cell::{Cell, Ref, RefCell};
 use std::rc::Rc;
 
+/// The byte order of serialized GVariant data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GvdbEndianness {
+    /// Little endian, the byte order GVDB files are usually written in
+    Little,
+
+    /// Big endian
+    Big,
+}
+
+impl GvdbEndianness {
+    /// The byte order of this machine
+    ///
+    /// ```
+    /// # use gvdb::write::GvdbEndianness;
+    /// #[cfg(target_endian = "little")]
+    /// assert_eq!(GvdbEndianness::native(), GvdbEndianness::Little);
+    /// ```
+    pub fn native() -> Self {
+        #[cfg(target_endian = "little")]
+        let native = Self::Little;
+        #[cfg(target_endian = "big")]
+        let native = Self::Big;
+        native
+    }
+
+    /// The byte order of data that is byteswapped on this machine if `byteswapped` is set
+    pub(crate) fn from_byteswapped(byteswapped: bool) -> Self {
+        match (Self::native(), byteswapped) {
+            (native, false) => native,
+            (Self::Little, true) => Self::Big,
+            (Self::Big, true) => Self::Little,
+        }
+    }
+
+    /// Whether data in this byte order has to be byteswapped on this machine
+    pub(crate) fn is_byteswapped(self) -> bool {
+        self != Self::native()
+    }
+}
+
 #[derive(Debug)]
 pub enum GvdbBuilderItemValue<'a> {
     // A zvariant::Value
@@ -12,6 +55,16 @@ pub enum GvdbBuilderItemValue<'a> {
     #[cfg(feature = "glib")]
     GVariant(glib::Variant),
 
+    // Serialized GVariant data of type `signature` that is written as it is
+    Raw {
+        signature: String,
+        bytes: Cow<'a, [u8]>,
+        endianness: GvdbEndianness,
+    },
+
+    // A value that is read from a reader when the file is written
+    Stream(GvdbValueStream<'a>),
+
     TableBuilder(GvdbHashTableBuilder<'a>),
 
     // A child container with no additional value
@@ -31,6 +84,8 @@ impl<'a> GvdbBuilderItemValue<'a> {
             GvdbBuilderItemValue::Value(_) => GvdbHashItemType::Value,
             #[cfg(feature = "glib")]
             GvdbBuilderItemValue::GVariant(_) => GvdbHashItemType::Value,
+            GvdbBuilderItemValue::Raw { .. } => GvdbHashItemType::Value,
+            GvdbBuilderItemValue::Stream(_) => GvdbHashItemType::Value,
             GvdbBuilderItemValue::TableBuilder(_) => GvdbHashItemType::HashTable,
             GvdbBuilderItemValue::Container(_) => GvdbHashItemType::Container,
         }
@@ -0,0 +1,155 @@
+use crate::write::hash::bloom_insert;
+
+/// An estimate of the lookup cost of a hash table with a specific layout
+///
+/// The values are calculated from the hashes of the actual keys in the table, not from a
+/// theoretical distribution.
+///
+/// ```
+/// # use gvdb::write::GvdbHashTableBuilder;
+/// let mut table_builder = GvdbHashTableBuilder::with_path_separator(None);
+/// for num in 0..100 {
+///     table_builder.insert(&format!("key{}", num), num).unwrap();
+/// }
+///
+/// table_builder.set_load_factor(4.0).unwrap();
+/// let estimate = table_builder.layout_estimate();
+/// assert_eq!(estimate.n_buckets, 25);
+/// assert!(estimate.mean_probe_length_hit >= 1.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct GvdbHashTableEstimate {
+    /// The number of items in the hash table, including implicitly created containers
+    pub n_items: usize,
+
+    /// The number of hash buckets
+    pub n_buckets: usize,
+
+    /// The number of 32 bit bloom filter words
+    pub n_bloom_words: u32,
+
+    /// The average number of items per bucket
+    pub load_factor: f64,
+
+    /// The average number of items compared when looking up a key that exists
+    pub mean_probe_length_hit: f64,
+
+    /// The average number of items compared when looking up a key that doesn't exist,
+    /// taking the bloom filter into account
+    pub mean_probe_length_miss: f64,
+
+    /// The number of items in the largest bucket
+    pub max_probe_length: usize,
+
+    /// The probability that the bloom filter lets a key pass that isn't in the table.
+    /// This is `1.0` if the table has no bloom filter.
+    pub bloom_false_positive_rate: f64,
+
+    /// The size of the hash table structure in bytes (header, bloom filter, buckets and items)
+    pub size: usize,
+}
+
+impl GvdbHashTableEstimate {
+    pub(crate) fn calculate(
+        hashes: &[u32],
+        n_buckets: usize,
+        n_bloom_words: u32,
+        bloom_shift: u32,
+        size: usize,
+    ) -> Self {
+        let n_items = hashes.len();
+
+        let mut bucket_sizes = vec![0usize; n_buckets];
+        let mut bloom_words = vec![0u32; n_bloom_words as usize];
+        for &hash_value in hashes {
+            if n_buckets > 0 {
+                bucket_sizes[hash_value as usize % n_buckets] += 1;
+            }
+
+            if !bloom_words.is_empty() {
+                bloom_insert(&mut bloom_words, bloom_shift, hash_value);
+            }
+        }
+
+        // A hit on the n-th item in a bucket requires n comparisons
+        let hit_comparisons: usize = bucket_sizes.iter().map(|s| s * (s + 1) / 2).sum();
+        let mean_probe_length_hit = if n_items > 0 {
+            hit_comparisons as f64 / n_items as f64
+        } else {
+            0.0
+        };
+
+        // Two random bits in the word have to be set for a false positive
+        let bloom_false_positive_rate = if bloom_words.is_empty() {
+            1.0
+        } else {
+            bloom_words
+                .iter()
+                .map(|word| (word.count_ones() as f64 / 32.0).powi(2))
+                .sum::<f64>()
+                / bloom_words.len() as f64
+        };
+
+        let load_factor = if n_buckets > 0 {
+            n_items as f64 / n_buckets as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            n_items,
+            n_buckets,
+            n_bloom_words,
+            load_factor,
+            mean_probe_length_hit,
+            mean_probe_length_miss: load_factor * bloom_false_positive_rate,
+            max_probe_length: bucket_sizes.iter().copied().max().unwrap_or(0),
+            bloom_false_positive_rate,
+            size,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GvdbHashTableEstimate;
+
+    #[test]
+    fn empty() {
+        let estimate = GvdbHashTableEstimate::calculate(&[], 0, 0, 5, 8);
+        assert_eq!(
+            estimate,
+            GvdbHashTableEstimate {
+                n_items: 0,
+                n_buckets: 0,
+                n_bloom_words: 0,
+                load_factor: 0.0,
+                mean_probe_length_hit: 0.0,
+                mean_probe_length_miss: 0.0,
+                max_probe_length: 0,
+                bloom_false_positive_rate: 1.0,
+                size: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn probe_lengths() {
+        // Buckets: [0, 2], [1], [], []
+        let estimate = GvdbHashTableEstimate::calculate(&[0, 4, 1], 4, 0, 5, 0);
+        assert_eq!(estimate.load_factor, 0.75);
+        assert_eq!(estimate.max_probe_length, 2);
+        assert_eq!(estimate.mean_probe_length_hit, 4.0 / 3.0);
+        assert_eq!(estimate.mean_probe_length_miss, 0.75);
+    }
+
+    #[test]
+    fn bloom_filter() {
+        // hash 0 sets bit 0 in word 0, hash 33 sets bit 1 in word 1
+        let estimate = GvdbHashTableEstimate::calculate(&[0, 33], 2, 2, 5, 0);
+        let expected = (1.0f64 / 32.0).powi(2);
+        assert_eq!(estimate.bloom_false_positive_rate, expected);
+        assert_eq!(estimate.mean_probe_length_miss, expected);
+    }
+}
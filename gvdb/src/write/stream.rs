@@ -0,0 +1,175 @@
+use crate::write::error::{GvdbBuilderResult, GvdbWriterError};
+#[cfg(feature = "gresource")]
+use std::borrow::Cow;
+use std::fmt::{Debug, Formatter};
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+use std::mem::size_of;
+
+/// Where the data of a [`GvdbValueStream`] comes from
+enum GvdbStreamSource<#[cfg(feature = "gresource")] 'a> {
+    /// Data that is already in memory
+    #[cfg(feature = "gresource")]
+    Data(Cow<'a, [u8]>),
+
+    /// Data that is read when the file is serialized
+    ///
+    /// Readers must not borrow anything, as the builder would otherwise have to be dropped before
+    /// the borrowed data.
+    Reader(Box<dyn Read>),
+}
+
+/// A value whose data is read from a [`Read`] source only when the file is serialized
+///
+/// The serialized value consists of the `u32` header fields, exactly `len` bytes from the reader
+/// and the variant type signature. The header fields are written in the byte order of the file.
+/// This covers byte arrays (`ay`) and tuples of fixed size fields that end with a byte array,
+/// like the `(uuay)` of GResource files, which have no framing offsets.
+pub(crate) struct GvdbValueStream<'a> {
+    header: Vec<u32>,
+    #[cfg(feature = "gresource")]
+    source: GvdbStreamSource<'a>,
+    #[cfg(not(feature = "gresource"))]
+    source: GvdbStreamSource,
+    len: u64,
+    signature: &'static str,
+    /// Only in-memory data is borrowed, which requires the `gresource` feature
+    lifetime: PhantomData<&'a [u8]>,
+}
+
+impl<'a> GvdbValueStream<'a> {
+    pub fn new(
+        header: Vec<u32>,
+        reader: impl Read + 'static,
+        len: u64,
+        signature: &'static str,
+    ) -> Self {
+        Self {
+            header,
+            source: GvdbStreamSource::Reader(Box::new(reader)),
+            len,
+            signature,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// A value with data that is already in memory
+    #[cfg(feature = "gresource")]
+    pub fn from_data(header: Vec<u32>, data: Cow<'a, [u8]>, signature: &'static str) -> Self {
+        Self {
+            header,
+            len: data.len() as u64,
+            source: GvdbStreamSource::Data(data),
+            signature,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// A byte array of `len` bytes from `reader`
+    pub fn bytes(reader: impl Read + 'static, len: u64) -> Self {
+        Self::new(Vec::new(), reader, len, "ay")
+    }
+
+    /// The size of the serialized value
+    pub fn size(&self) -> GvdbBuilderResult<usize> {
+        usize::try_from(self.len)
+            .ok()
+            .and_then(|len| {
+                len.checked_add(self.header.len() * size_of::<u32>() + 1 + self.signature.len())
+            })
+            .ok_or_else(|| {
+                GvdbWriterError::FileSize(format!(
+                    "Value of {} bytes does not fit into memory",
+                    self.len
+                ))
+            })
+    }
+
+    /// Serialize the value into `writer`, byteswapping the header fields if requested
+    pub fn write(mut self, writer: &mut dyn Write, byteswap: bool) -> GvdbBuilderResult<()> {
+        for field in &self.header {
+            let field = if byteswap { field.swap_bytes() } else { *field };
+            writer.write_all(&field.to_ne_bytes())?;
+        }
+
+        let copied = match &mut self.source {
+            #[cfg(feature = "gresource")]
+            GvdbStreamSource::Data(data) => {
+                writer.write_all(data)?;
+                data.len() as u64
+            }
+            GvdbStreamSource::Reader(reader) => std::io::copy(&mut reader.take(self.len), writer)?,
+        };
+        if copied != self.len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("Value data ended after {} of {} bytes", copied, self.len),
+            )
+            .into());
+        }
+
+        writer.write_all(&[0])?;
+        writer.write_all(self.signature.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl<'a> Debug for GvdbValueStream<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GvdbValueStream")
+            .field("header", &self.header)
+            .field("len", &self.len)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::GvdbFile;
+    use matches::assert_matches;
+
+    #[test]
+    fn write() {
+        let stream = GvdbValueStream::new(vec![3, 1], &b"abc"[..], 3, "(uuay)");
+        assert_eq!(stream.size().unwrap(), 8 + 3 + 1 + 6);
+        assert!(format!("{:?}", stream).contains("(uuay)"));
+
+        let mut data = Vec::new();
+        stream.write(&mut data, false).unwrap();
+        assert_eq!(data.len(), 18);
+
+        let value = GvdbFile::parse_value(&data, false).unwrap();
+        let (size, flags, bytes): (u32, u32, Vec<u8>) = value.try_into().unwrap();
+        assert_eq!((size, flags, &bytes[..]), (3, 1, &b"abc"[..]));
+
+        let mut swapped = Vec::new();
+        GvdbValueStream::new(vec![3, 1], &b"abc"[..], 3, "(uuay)")
+            .write(&mut swapped, true)
+            .unwrap();
+        assert_eq!(&swapped[..4], &3u32.swap_bytes().to_ne_bytes());
+        assert_eq!(&swapped[8..], &data[8..]);
+    }
+
+    #[test]
+    fn short_read() {
+        let stream = GvdbValueStream::bytes(&b"abc"[..], 4);
+        let err = stream.write(&mut Vec::new(), false).unwrap_err();
+        assert_matches!(err, GvdbWriterError::Io(_, None));
+        assert!(format!("{}", err).contains("3 of 4 bytes"));
+
+        // Additional data of the reader is ignored
+        let mut data = Vec::new();
+        GvdbValueStream::bytes(&b"abc"[..], 2)
+            .write(&mut data, false)
+            .unwrap();
+        assert_eq!(data, b"ab\0ay");
+    }
+
+    #[test]
+    fn size() {
+        let stream = GvdbValueStream::bytes(&b""[..], u64::MAX);
+        assert_matches!(stream.size(), Err(GvdbWriterError::FileSize(_)));
+    }
+}
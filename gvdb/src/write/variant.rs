@@ -0,0 +1,613 @@
+use std::ops::Range;
+
+/// The maximum nesting depth of containers in a value, like `G_VARIANT_MAX_RECURSION_DEPTH`
+const MAX_DEPTH: usize = 128;
+
+/// The kind of a [`VariantType`]
+#[derive(Debug)]
+enum VariantKind {
+    /// A boolean, serialized as a single byte
+    Bool,
+
+    /// A number of 1, 2, 4 or 8 bytes, stored in the byte order of the value
+    Number(usize),
+
+    /// A nul-terminated string (`s`), object path (`o`) or signature (`g`)
+    String(u8),
+
+    /// A value of any type, followed by a nul byte and its type signature
+    Variant,
+
+    /// A value that may be absent
+    Maybe(Box<VariantType>),
+
+    /// An array of values of the same type
+    Array(Box<VariantType>),
+
+    /// A tuple or dict entry
+    Tuple(Vec<VariantType>),
+}
+
+/// A GVariant type with the layout information needed to walk its serialized data
+#[derive(Debug)]
+struct VariantType {
+    kind: VariantKind,
+    alignment: usize,
+    fixed_size: Option<usize>,
+}
+
+impl VariantType {
+    /// Parse a signature that consists of exactly one complete type
+    fn parse(signature: &str, depth: usize) -> Result<Self, String> {
+        let mut pos = 0;
+        let typ = Self::parse_one(signature.as_bytes(), &mut pos, depth)?;
+        if pos != signature.len() {
+            return Err(format!(
+                "Type signature '{}' is not a single complete type",
+                signature
+            ));
+        }
+
+        Ok(typ)
+    }
+
+    fn parse_one(signature: &[u8], pos: &mut usize, depth: usize) -> Result<Self, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "Type signature is nested deeper than {} levels",
+                MAX_DEPTH
+            ));
+        }
+
+        let char = *signature.get(*pos).ok_or_else(|| {
+            format!(
+                "Type signature '{}' ends before the type is complete",
+                String::from_utf8_lossy(signature)
+            )
+        })?;
+        *pos += 1;
+
+        let kind = match char {
+            b'b' => VariantKind::Bool,
+            b'y' => VariantKind::Number(1),
+            b'n' | b'q' => VariantKind::Number(2),
+            b'i' | b'u' | b'h' => VariantKind::Number(4),
+            b'x' | b't' | b'd' => VariantKind::Number(8),
+            b's' | b'o' | b'g' => VariantKind::String(char),
+            b'v' => VariantKind::Variant,
+            b'm' => VariantKind::Maybe(Box::new(Self::parse_one(signature, pos, depth + 1)?)),
+            b'a' => VariantKind::Array(Box::new(Self::parse_one(signature, pos, depth + 1)?)),
+            b'(' => {
+                let mut members = Vec::new();
+                while signature.get(*pos) != Some(&b')') {
+                    members.push(Self::parse_one(signature, pos, depth + 1)?);
+                }
+                *pos += 1;
+                VariantKind::Tuple(members)
+            }
+            b'{' => {
+                let key = Self::parse_one(signature, pos, depth + 1)?;
+                if !matches!(
+                    key.kind,
+                    VariantKind::Bool | VariantKind::Number(_) | VariantKind::String(_)
+                ) {
+                    return Err("The key of a dict entry must be a basic type".to_string());
+                }
+
+                let value = Self::parse_one(signature, pos, depth + 1)?;
+                if signature.get(*pos) != Some(&b'}') {
+                    return Err("A dict entry must contain exactly two types".to_string());
+                }
+                *pos += 1;
+                VariantKind::Tuple(vec![key, value])
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid character '{}' in type signature",
+                    char::from(char)
+                ))
+            }
+        };
+
+        Ok(Self::new(kind))
+    }
+
+    fn new(kind: VariantKind) -> Self {
+        let (alignment, fixed_size) = match &kind {
+            VariantKind::Bool => (1, Some(1)),
+            VariantKind::Number(size) => (*size, Some(*size)),
+            VariantKind::String(_) => (1, None),
+            VariantKind::Variant => (8, None),
+            VariantKind::Maybe(child) | VariantKind::Array(child) => (child.alignment, None),
+            VariantKind::Tuple(members) => {
+                let alignment = members.iter().map(|m| m.alignment).max().unwrap_or(1);
+                let mut size = Some(0);
+                for member in members {
+                    size = size
+                        .zip(member.fixed_size)
+                        .map(|(size, member_size)| align(size, member.alignment) + member_size);
+                }
+
+                // The unit type takes up a single byte
+                let fixed_size = size.map(|size| align(size.max(1), alignment));
+                (alignment, fixed_size)
+            }
+        };
+
+        Self {
+            kind,
+            alignment,
+            fixed_size,
+        }
+    }
+}
+
+/// Round `offset` up to a multiple of `alignment`, which is a power of two
+fn align(offset: usize, alignment: usize) -> usize {
+    (offset + alignment - 1) & !(alignment - 1)
+}
+
+/// The size of the framing offsets in a container of `size` bytes
+fn offset_size(size: usize) -> usize {
+    if size == 0 {
+        0
+    } else if size <= u8::MAX as usize {
+        1
+    } else if size <= u16::MAX as usize {
+        2
+    } else if size as u64 <= u32::MAX as u64 {
+        4
+    } else {
+        8
+    }
+}
+
+/// Serialized data that is walked, and byteswapped if it can be modified
+trait VariantData {
+    fn bytes(&self) -> &[u8];
+
+    fn swap(&mut self, range: Range<usize>);
+}
+
+impl VariantData for &[u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn swap(&mut self, _range: Range<usize>) {}
+}
+
+impl VariantData for &mut [u8] {
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn swap(&mut self, range: Range<usize>) {
+        self[range].reverse();
+    }
+}
+
+/// Walks serialized GVariant data along its type
+///
+/// The data is checked to be consistent with the type, like the sizes of fixed size values and
+/// the framing offsets of containers. Framing offsets are always little endian, so the data can
+/// be walked independently of its byte order. If `strict` is set, the data also has to be in
+/// normal form, which is the only form GVDB files should contain.
+struct VariantWalker<D> {
+    data: D,
+    strict: bool,
+}
+
+impl<D: VariantData> VariantWalker<D> {
+    fn walk(&mut self, typ: &VariantType, range: Range<usize>, depth: usize) -> Result<(), String> {
+        let size = range.len();
+        if let Some(fixed_size) = typ.fixed_size {
+            if size != fixed_size {
+                return Err(format!(
+                    "Expected {} bytes for a fixed size value, got {}",
+                    fixed_size, size
+                ));
+            }
+        }
+
+        match &typ.kind {
+            VariantKind::Bool => {
+                if self.strict && self.data.bytes()[range.start] > 1 {
+                    return Err("Boolean values have to be 0 or 1".to_string());
+                }
+                Ok(())
+            }
+            VariantKind::Number(size) => {
+                if *size > 1 {
+                    self.data.swap(range);
+                }
+                Ok(())
+            }
+            VariantKind::String(kind) => self.check_string(*kind, range),
+            VariantKind::Variant => self.walk_variant(range, depth),
+            VariantKind::Maybe(child) => {
+                if size == 0 {
+                    // Nothing
+                    Ok(())
+                } else if child.fixed_size.is_some() {
+                    self.walk(child, range, depth)
+                } else {
+                    // Variable size values are followed by a nul byte
+                    self.check_padding(range.end - 1..range.end)?;
+                    self.walk(child, range.start..range.end - 1, depth)
+                }
+            }
+            VariantKind::Array(element) => self.walk_array(element, range, depth),
+            VariantKind::Tuple(members) => self.walk_tuple(members, typ.fixed_size, range, depth),
+        }
+    }
+
+    fn check_padding(&self, range: Range<usize>) -> Result<(), String> {
+        if self.strict && self.data.bytes()[range].iter().any(|byte| *byte != 0) {
+            return Err("Padding bytes have to be zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Read the little endian framing offset at `pos`
+    fn read_offset(&self, pos: usize, offset_size: usize) -> usize {
+        self.data.bytes()[pos..pos + offset_size]
+            .iter()
+            .rev()
+            .fold(0u64, |offset, byte| offset << 8 | *byte as u64) as usize
+    }
+
+    fn check_string(&self, kind: u8, range: Range<usize>) -> Result<(), String> {
+        let bytes = &self.data.bytes()[range];
+        let (last, string) = bytes
+            .split_last()
+            .ok_or_else(|| "Strings have to be nul-terminated".to_string())?;
+        if *last != 0 {
+            return Err("Strings have to be nul-terminated".to_string());
+        }
+        if !self.strict {
+            return Ok(());
+        }
+
+        let string = std::str::from_utf8(string)
+            .ok()
+            .filter(|string| !string.contains('\0'))
+            .ok_or_else(|| "Strings have to be valid UTF-8 without nul bytes".to_string())?;
+        let valid = match kind {
+            b'o' => is_object_path(string),
+            b'g' => is_signature(string),
+            _ => true,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' is not a valid value of type '{}'",
+                string,
+                char::from(kind)
+            ))
+        }
+    }
+
+    fn walk_variant(&mut self, range: Range<usize>, depth: usize) -> Result<(), String> {
+        let nul = self.data.bytes()[range.clone()]
+            .iter()
+            .rposition(|byte| *byte == 0)
+            .ok_or_else(|| "Variant without type signature".to_string())?;
+        let signature = std::str::from_utf8(&self.data.bytes()[range.start + nul + 1..range.end])
+            .map_err(|_| "Invalid type signature of variant".to_string())?;
+
+        let typ = VariantType::parse(signature, depth + 1)?;
+        self.walk(&typ, range.start..range.start + nul, depth + 1)
+    }
+
+    fn walk_array(
+        &mut self,
+        element: &VariantType,
+        range: Range<usize>,
+        depth: usize,
+    ) -> Result<(), String> {
+        let size = range.len();
+        if size == 0 {
+            return Ok(());
+        }
+
+        if let Some(element_size) = element.fixed_size {
+            if size % element_size != 0 {
+                return Err(format!(
+                    "Array of {} bytes is not a multiple of its element size {}",
+                    size, element_size
+                ));
+            }
+
+            for start in range.step_by(element_size) {
+                self.walk(element, start..start + element_size, depth)?;
+            }
+            return Ok(());
+        }
+
+        // The offsets of the ends of all elements follow the elements
+        let offset_size = offset_size(size);
+        if size < offset_size {
+            return Err("Array too small for its framing offsets".to_string());
+        }
+        let offsets_start = self.read_offset(range.end - offset_size, offset_size);
+        if offsets_start > size - offset_size || (size - offsets_start) % offset_size != 0 {
+            return Err("Invalid framing offsets of array".to_string());
+        }
+
+        let mut end = 0;
+        for pos in (range.start + offsets_start..range.end).step_by(offset_size) {
+            let start = align(end, element.alignment);
+            let element_end = self.read_offset(pos, offset_size);
+            if start > element_end || element_end > offsets_start {
+                return Err("Invalid framing offset of array element".to_string());
+            }
+
+            self.check_padding(range.start + end..range.start + start)?;
+            self.walk(
+                element,
+                range.start + start..range.start + element_end,
+                depth,
+            )?;
+            end = element_end;
+        }
+
+        Ok(())
+    }
+
+    fn walk_tuple(
+        &mut self,
+        members: &[VariantType],
+        fixed_size: Option<usize>,
+        range: Range<usize>,
+        depth: usize,
+    ) -> Result<(), String> {
+        let size = range.len();
+        let offset_size = if fixed_size.is_some() {
+            0
+        } else {
+            offset_size(size)
+        };
+
+        // The framing offsets of all variable size members but the last one are stored in
+        // reverse order at the end of the tuple
+        let mut offsets_start = size;
+        let mut end = 0;
+        for (index, member) in members.iter().enumerate() {
+            let start = align(end, member.alignment);
+            let member_end = if let Some(member_size) = member.fixed_size {
+                start + member_size
+            } else if index == members.len() - 1 {
+                offsets_start
+            } else {
+                if offsets_start < offset_size {
+                    return Err("Tuple too small for its framing offsets".to_string());
+                }
+                offsets_start -= offset_size;
+                self.read_offset(range.start + offsets_start, offset_size)
+            };
+
+            if start > member_end || member_end > offsets_start {
+                return Err("Invalid framing offset of tuple member".to_string());
+            }
+
+            self.check_padding(range.start + end..range.start + start)?;
+            self.walk(member, range.start + start..range.start + member_end, depth)?;
+            end = member_end;
+        }
+
+        // Fixed size tuples are padded to their alignment, others end with their last member
+        if fixed_size.is_some() {
+            self.check_padding(range.start + end..range.end)
+        } else if self.strict && end != offsets_start {
+            Err("Unused data in tuple".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Whether `string` is a valid D-Bus object path
+fn is_object_path(string: &str) -> bool {
+    string == "/"
+        || string.strip_prefix('/').map_or(false, |path| {
+            path.split('/').all(|element| {
+                !element.is_empty()
+                    && element
+                        .bytes()
+                        .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+            })
+        })
+}
+
+/// Whether `string` is a sequence of complete types
+fn is_signature(string: &str) -> bool {
+    let mut pos = 0;
+    while pos < string.len() {
+        if VariantType::parse_one(string.as_bytes(), &mut pos, 0).is_err() {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Swap the byte order of the serialized GVariant `data` of type `signature` in place, like
+/// `g_variant_byteswap()`
+///
+/// All numbers of more than one byte are byteswapped, including the values of nested variants.
+/// Framing offsets are always little endian and stay as they are.
+pub(crate) fn byteswap(signature: &str, data: &mut [u8]) -> Result<(), String> {
+    let typ = VariantType::parse(signature, 0)?;
+    let range = 0..data.len();
+    VariantWalker {
+        data,
+        strict: false,
+    }
+    .walk(&typ, range, 0)
+}
+
+/// Check that `data` is a value of type `signature` in normal form
+///
+/// The signature has to be a single complete type. The data is checked without deserializing
+/// it, its byte order doesn't matter.
+pub(crate) fn validate(signature: &str, data: &[u8]) -> Result<(), String> {
+    let typ = VariantType::parse(signature, 0)?;
+    VariantWalker { data, strict: true }.walk(&typ, 0..data.len(), 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::read::GvdbFile;
+    use zvariant::{EncodingContext, Value};
+
+    /// Serialize `value` as variant in little and big endian and return the data of the value
+    /// itself and its signature
+    fn serialize(value: Value) -> (Vec<u8>, Vec<u8>, String) {
+        let le =
+            zvariant::to_bytes(EncodingContext::<byteorder::LE>::new_gvariant(0), &value).unwrap();
+        let be =
+            zvariant::to_bytes(EncodingContext::<byteorder::BE>::new_gvariant(0), &value).unwrap();
+        let signature = value.value_signature().to_string();
+        let len = le.len() - signature.len() - 1;
+        (le[..len].to_vec(), be[..len].to_vec(), signature)
+    }
+
+    fn values() -> Vec<Value<'static>> {
+        vec![
+            Value::from(42u32),
+            Value::from(-42i64),
+            Value::from(1.5f64),
+            Value::from("test"),
+            Value::from(vec!["a", "bcd", ""]),
+            Value::from(vec![1u16, 2, 3]),
+            Value::Value(Box::new(Value::from(42u32))),
+            Value::from((1u8, 2u16, "test", 4u64)),
+            Value::from((3u32, 1u32, vec![1u8, 2, 3])),
+            Value::from(vec![(1u32, "a".to_string()), (2u32, "bc".to_string())]),
+            Value::from(zvariant::ObjectPath::try_from("/org/gnome").unwrap()),
+            Value::from(zvariant::Signature::try_from("a{sv}").unwrap()),
+        ]
+    }
+
+    #[test]
+    fn byteswap_values() {
+        for value in values() {
+            let (le, be, signature) = serialize(value);
+            let mut swapped = le.clone();
+            byteswap(&signature, &mut swapped).unwrap();
+            assert_eq!(swapped, be, "{}", signature);
+            byteswap(&signature, &mut swapped).unwrap();
+            assert_eq!(swapped, le, "{}", signature);
+        }
+    }
+
+    #[test]
+    fn byteswap_dict() {
+        // {'a': <uint16 1>} as serialized by GLib
+        let mut data = b"a\0\0\0\0\0\0\0\x01\0\0q\x02\x0d".to_vec();
+        byteswap("a{sv}", &mut data).unwrap();
+        assert_eq!(data, b"a\0\0\0\0\0\0\0\0\x01\0q\x02\x0d");
+    }
+
+    #[test]
+    fn byteswap_bool() {
+        // zvariant serializes booleans with 4 bytes, GVariant uses a single byte
+        let mut data = vec![1, 0, 0, 0, 0, 0, 0, 42];
+        byteswap("(bbu)", &mut data).unwrap();
+        assert_eq!(data, [1, 0, 0, 0, 42, 0, 0, 0]);
+        assert!(byteswap("(bbu)", &mut data[..7]).is_err());
+    }
+
+    #[test]
+    fn byteswap_file() {
+        // test2.gvdb is big endian
+        let file = GvdbFile::from_file(&crate::test::TEST_FILE_2).unwrap();
+        let root = file.hash_table().unwrap();
+        let table = root.get_hash_table("table").unwrap();
+        let info = table.get_item_info("int").unwrap();
+        let mut data = info.value_bytes().unwrap().to_vec();
+        byteswap(info.signature().unwrap(), &mut data).unwrap();
+        assert_eq!(data, 42u32.to_le_bytes());
+    }
+
+    #[test]
+    fn layout() {
+        let typ = VariantType::parse("(yqs)", 0).unwrap();
+        assert_eq!((typ.alignment, typ.fixed_size), (2, None));
+        let typ = VariantType::parse("(yu)", 0).unwrap();
+        assert_eq!((typ.alignment, typ.fixed_size), (4, Some(8)));
+        let typ = VariantType::parse("(ty)", 0).unwrap();
+        assert_eq!((typ.alignment, typ.fixed_size), (8, Some(16)));
+        let typ = VariantType::parse("()", 0).unwrap();
+        assert_eq!((typ.alignment, typ.fixed_size), (1, Some(1)));
+        let typ = VariantType::parse("a{sv}", 0).unwrap();
+        assert_eq!((typ.alignment, typ.fixed_size), (8, None));
+    }
+
+    #[test]
+    fn invalid_signature() {
+        for signature in ["", "uu", "a", "(u", "{vs}", "{s}", "{sss}", "z", "u)"] {
+            assert!(VariantType::parse(signature, 0).is_err(), "{}", signature);
+        }
+
+        let deep = "a".repeat(MAX_DEPTH + 1) + "u";
+        assert!(VariantType::parse(&deep, 0).is_err());
+    }
+
+    #[test]
+    fn validate_values() {
+        for value in values() {
+            let (le, be, signature) = serialize(value);
+            validate(&signature, &le).unwrap();
+            validate(&signature, &be).unwrap();
+        }
+
+        validate("a{sv}", b"a\0\0\0\0\0\0\0\x01\0\0q\x02\x0d").unwrap();
+        validate("()", &[0]).unwrap();
+        validate("ms", b"").unwrap();
+        validate("ms", b"a\0\0").unwrap();
+        validate("mu", &[1, 0, 0, 0]).unwrap();
+        validate("b", &[1]).unwrap();
+        validate("o", b"/\0").unwrap();
+    }
+
+    #[test]
+    fn validate_normal_form() {
+        // Booleans other than 0 and 1
+        assert!(validate("b", &[2]).is_err());
+        // Non-zero padding
+        assert!(validate("(yu)", &[1, 1, 0, 0, 42, 0, 0, 0]).is_err());
+        assert!(validate("(yu)", &[1, 0, 0, 0, 42, 0, 0, 0]).is_ok());
+        // Strings with nul bytes or invalid UTF-8
+        assert!(validate("s", b"a\0b\0").is_err());
+        assert!(validate("s", b"\xff\0").is_err());
+        assert!(validate("o", b"org\0").is_err());
+        assert!(validate("o", b"/org/\0").is_err());
+        assert!(validate("g", b"a{sv}\0").is_ok());
+        assert!(validate("g", b"a{\0").is_err());
+        // Maybe of variable size without the trailing nul byte
+        assert!(validate("ms", b"a\0\x01").is_err());
+        // Unused data between the last tuple member and the framing offsets
+        assert!(validate("(sy)", b"a\0\x01\0\x02").is_err());
+        // Invalid type signature of a nested variant
+        assert!(validate("v", b"\0\0uu").is_err());
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(byteswap("u", &mut [1, 2, 3, 4, 5]).is_err());
+        assert!(byteswap("au", &mut [1, 2, 3, 4, 5]).is_err());
+        assert!(byteswap("s", &mut []).is_err());
+        assert!(byteswap("s", &mut b"abc".to_vec()).is_err());
+        assert!(byteswap("v", &mut b"abc".to_vec()).is_err());
+        assert!(byteswap("v", &mut b"\0z".to_vec()).is_err());
+        // Framing offset beyond the end of the array
+        assert!(byteswap("as", &mut b"a\0\x05".to_vec()).is_err());
+        // Framing offset beyond the end of the tuple
+        assert!(byteswap("(ss)", &mut b"a\0b\0\x09".to_vec()).is_err());
+    }
+}